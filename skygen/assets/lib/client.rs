@@ -15,32 +15,72 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::errors::{Error, Result};
+use crate::auth::Credentials;
+use crate::credentials::{ChainProvider, CredentialProvider};
+use crate::errors::{ApiError, Error, Result};
+use crate::rate_limit::{RateLimitInfo, RateLimitState, Throttle};
+use crate::retry::{is_idempotent, is_retryable, RetryPolicy};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::{HeaderName, HeaderValue, CONTENT_TYPE};
 use reqwest::redirect::Policy;
-use reqwest::{Client as ReqwestClient, ClientBuilder, Request, Response, Url};
+use reqwest::{Client as ReqwestClient, Method, Request, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
 
-#[derive(Clone)]
+/// The client handed to every generated operation.
+pub type ApiClient = Client;
+
+/// Result of a generated operation.
+pub type ApiResult<T> = Result<T>;
+
+/// Everything outside the RFC 3986 unreserved set is escaped in path segments.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Where the secret of a [`Credentials`] goes in a request.
+pub enum AuthLocation {
+    Header { name: &'static str, value: String },
+    Query { name: &'static str, value: String },
+}
+
+#[derive(Clone, Debug)]
 pub struct Client {
     base: Url,
     inner: ReqwestClient,
+    credentials: Vec<Credentials>,
+    retry: RetryPolicy,
+    throttle: Option<Throttle>,
+    rate_limit: RateLimitState,
+}
+
+/// A decoded response along with what the API reported about it.
+#[derive(Debug, Clone)]
+pub struct ApiResponse<T> {
+    pub value: T,
+    pub status: StatusCode,
+    pub rate_limit: Option<RateLimitInfo>,
 }
 
 impl Client {
     /// Build a client pointed at the provider’s default base URL.
     pub fn with_default_transport() -> Result<Self> {
-        let base =
-            reqwest::Url::parse(crate::API_URL).map_err(|e| Error::Transport(Box::new(e)))?;
-
-        let inner = Self::builder()
-            .build()
-            .map_err(|e| Error::Transport(Box::new(e)))?;
-
-        Ok(Self { base, inner })
+        Self::builder().build()
     }
 
     /// Override the base URL / transport if desired.
     pub fn new(base: reqwest::Url, inner: reqwest::Client) -> Self {
-        Self { base, inner }
+        Self {
+            base,
+            inner,
+            credentials: Vec::new(),
+            retry: RetryPolicy::default(),
+            throttle: None,
+            rate_limit: RateLimitState::default(),
+        }
     }
 
     pub fn base(&self) -> &Url {
@@ -48,25 +88,146 @@ impl Client {
     }
 
     pub fn builder() -> ClientBuilder {
-        ReqwestClient::builder().redirect(Policy::none())
+        ClientBuilder::default()
+    }
+
+    /// The quota reported by the latest response carrying rate limit headers.
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        self.rate_limit.current()
     }
 
-    /// Join `path_and_query` (starting with '/') to the base URL.
+    /// Append `path_and_query` (starting with '/') to the path of the base
+    /// URL, which `Url::join` would replace.
     ///
-    /// Example: "/client/v4/zones?per_page=50"
+    /// Example: "/zones?per_page=50" on "https://api.cloudflare.com/client/v4"
     pub fn join_url(&self, path_and_query: &str) -> Result<Url> {
-        self.base
-            .join(path_and_query)
-            .map_err(|e| Error::Transport(Box::new(e)))
+        let (path, query) = match path_and_query.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (path_and_query, None),
+        };
+
+        let mut url = self.base.clone();
+        let base = self.base.path().trim_end_matches('/');
+        url.set_path(&format!("{base}/{}", path.trim_start_matches('/')));
+        if query.is_some() {
+            url.set_query(query);
+        }
+        Ok(url)
     }
 
+    /// Authorize `req` with the first alternative of `security` whose schemes
+    /// all have credentials. An empty `security` needs none, and an empty
+    /// alternative lets the request go out anonymously when no other one can
+    /// be satisfied.
+    pub fn authorize(
+        &self,
+        req: &mut Request,
+        security: &'static [&'static [&'static str]],
+    ) -> Result<()> {
+        if security.is_empty() {
+            return Ok(());
+        }
+
+        let credentials = security
+            .iter()
+            .filter(|schemes| !schemes.is_empty())
+            .find_map(|schemes| {
+                schemes
+                    .iter()
+                    .map(|scheme| self.credentials.iter().find(|c| c.scheme() == *scheme))
+                    .collect::<Option<Vec<_>>>()
+            });
+        let credentials = match credentials {
+            Some(credentials) => credentials,
+            None if security.iter().any(|schemes| schemes.is_empty()) => return Ok(()),
+            None => return Err(Error::MissingCredentials(security)),
+        };
+
+        for credential in credentials {
+            match credential.location() {
+                AuthLocation::Header { name, value } => {
+                    let header = HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|_| Error::InvalidHeader(name.to_string()))?;
+                    let mut value = HeaderValue::from_str(&value)
+                        .map_err(|_| Error::InvalidHeader(name.to_string()))?;
+                    // Keeps the secret out of `Debug` output and HTTP/2
+                    // header compression.
+                    value.set_sensitive(true);
+                    req.headers_mut().insert(header, value);
+                }
+                AuthLocation::Query { name, value } => {
+                    req.url_mut().query_pairs_mut().append_pair(name, &value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send `req`, retrying it as the [`RetryPolicy`] allows when its method
+    /// is idempotent.
     pub async fn send(&self, req: Request) -> Result<Response> {
+        let retryable = is_idempotent(req.method());
+        self.send_retrying(req, retryable).await
+    }
+
+    /// Send `req`, retrying it as the [`RetryPolicy`] allows when
+    /// `retryable`, whatever its method.
+    pub async fn send_retrying(&self, mut req: Request, retryable: bool) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            // Requests with a streaming body cannot be cloned and go out once.
+            let next = if retryable && self.retry.allows(attempt) {
+                req.try_clone()
+            } else {
+                None
+            };
+            let outcome = self.execute(req).await;
+            let Some(next) = next else {
+                return Ok(outcome?);
+            };
+
+            let delay = match &outcome {
+                Ok(resp) if is_retryable(resp.status()) => self
+                    .retry
+                    .delay(attempt, Some((resp.status(), resp.headers()))),
+                Err(e) if e.is_connect() || e.is_timeout() => self.retry.delay(attempt, None),
+                _ => return Ok(outcome?),
+            };
+
+            #[cfg(feature = "tracing")]
+            {
+                tracing::debug!(attempt, delay = ?delay, "retrying request");
+            }
+
+            tokio::time::sleep(delay).await;
+            req = next;
+            attempt += 1;
+        }
+    }
+
+    async fn execute(&self, req: Request) -> reqwest::Result<Response> {
+        if let Some(throttle) = &self.throttle {
+            let delay = self.rate_limit.acquire(throttle);
+            if !delay.is_zero() {
+                #[cfg(feature = "tracing")]
+                {
+                    tracing::debug!(delay = ?delay, "throttling request");
+                }
+                tokio::time::sleep(delay).await;
+            }
+        }
+
         #[cfg(feature = "tracing")]
         {
-            tracing::debug!(method = %req.method(), url = %req.url(), "sending request");
+            // Only the path, since the query may carry an API key.
+            tracing::debug!(method = %req.method(), path = req.url().path(), "sending request");
         }
 
         let resp = self.inner.execute(req).await?;
+        if let Some(info) = RateLimitInfo::from_headers(resp.headers()) {
+            self.rate_limit.update(info);
+        }
 
         #[cfg(feature = "tracing")]
         {
@@ -76,3 +237,360 @@ impl Client {
         Ok(resp)
     }
 }
+
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    base: Option<Url>,
+    inner: Option<ReqwestClient>,
+    credentials: Vec<Credentials>,
+    provider: Option<Box<dyn CredentialProvider>>,
+    retry: Option<RetryPolicy>,
+    throttle: Option<Throttle>,
+}
+
+impl ClientBuilder {
+    /// Send requests somewhere other than the provider’s default base URL.
+    pub fn base_url(mut self, base: Url) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Use a preconfigured reqwest client instead of the default transport.
+    pub fn http_client(mut self, inner: ReqwestClient) -> Self {
+        self.inner = Some(inner);
+        self
+    }
+
+    /// Authenticate with `credentials` wherever an operation accepts their
+    /// scheme. Call once per scheme; a later call for the same scheme wins.
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials
+            .retain(|c| c.scheme() != credentials.scheme());
+        self.credentials.push(credentials);
+        self
+    }
+
+    /// Load the credentials of every scheme not set with
+    /// [`credentials`](Self::credentials) from `provider` when building.
+    /// Without one, [`ChainProvider::standard`] is asked unless credentials
+    /// were set.
+    pub fn credential_provider(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.provider = Some(Box::new(provider));
+        self
+    }
+
+    /// Retry failed requests as `policy` says, [`RetryPolicy::default`]
+    /// otherwise.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Slow down ahead of the rate limit as `throttle` says, instead of
+    /// only backing off once requests are rejected.
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let base = match self.base {
+            Some(base) => base,
+            None => Url::parse(crate::API_URL).map_err(|e| Error::Transport(Box::new(e)))?,
+        };
+
+        let inner = match self.inner {
+            Some(inner) => inner,
+            None => ReqwestClient::builder().redirect(Policy::none()).build()?,
+        };
+
+        let mut credentials = self.credentials;
+        let provider = match self.provider {
+            Some(provider) => Some(provider),
+            None if credentials.is_empty() => Some(Box::new(ChainProvider::standard()) as _),
+            None => None,
+        };
+        if let Some(provider) = provider {
+            for scheme in Credentials::SCHEMES {
+                if credentials.iter().all(|c| c.scheme() != *scheme) {
+                    credentials.extend(provider.credentials(scheme));
+                }
+            }
+        }
+
+        Ok(Client {
+            base,
+            inner,
+            credentials,
+            retry: self.retry.unwrap_or_default(),
+            throttle: self.throttle,
+            rate_limit: RateLimitState::default(),
+        })
+    }
+}
+
+/// Collects the parameters of a single operation and decodes its response
+/// into `T`.
+#[derive(Debug, Clone)]
+pub struct ApiRequestBuilder<'a, T> {
+    client: &'a Client,
+    method: Method,
+    path: &'static str,
+    path_params: Vec<(&'static str, String)>,
+    query: Vec<(&'static str, String)>,
+    headers: Vec<(&'static str, String)>,
+    body: Option<Vec<u8>>,
+    /// Why the body set by [`body`](Self::body) could not be serialized.
+    body_error: Option<String>,
+    required_path: Vec<&'static str>,
+    required_query: Vec<&'static str>,
+    required_headers: Vec<&'static str>,
+    body_required: bool,
+    security: &'static [&'static [&'static str]],
+    retry_safe: bool,
+    empty_response: bool,
+    #[cfg(feature = "validation")]
+    invalid: Option<crate::validation::ValidationError>,
+    response: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ApiRequestBuilder<'a, T> {
+    /// `path` is the templated operation path, e.g. `/v2/droplets/{droplet_id}`.
+    pub fn new(client: &'a Client, method: Method, path: &'static str) -> Self {
+        Self {
+            client,
+            method,
+            path,
+            path_params: Vec::new(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            body_error: None,
+            required_path: Vec::new(),
+            required_query: Vec::new(),
+            required_headers: Vec::new(),
+            body_required: false,
+            security: &[],
+            retry_safe: false,
+            empty_response: false,
+            #[cfg(feature = "validation")]
+            invalid: None,
+            response: PhantomData,
+        }
+    }
+
+    pub fn require_path(mut self, name: &'static str) -> Self {
+        self.required_path.push(name);
+        self
+    }
+
+    pub fn require_query(mut self, name: &'static str) -> Self {
+        self.required_query.push(name);
+        self
+    }
+
+    pub fn require_header(mut self, name: &'static str) -> Self {
+        self.required_headers.push(name);
+        self
+    }
+
+    pub fn require_body(mut self) -> Self {
+        self.body_required = true;
+        self
+    }
+
+    /// Alternative sets of security schemes the operation accepts, e.g.
+    /// `&[&["api_email", "api_key"], &["api_token"]]`.
+    pub fn security(mut self, security: &'static [&'static [&'static str]]) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Retry the operation like an idempotent one, e.g. a `POST` that is
+    /// safe to repeat.
+    pub fn retry_safe(mut self) -> Self {
+        self.retry_safe = true;
+        self
+    }
+
+    /// The operation returns no JSON payload: whatever body the server sends
+    /// back is discarded and decoded like an empty one.
+    pub fn empty_response(mut self) -> Self {
+        self.empty_response = true;
+        self
+    }
+
+    pub fn path_param(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.path_params.retain(|(n, _)| *n != name);
+        self.path_params.push((name, value.into()));
+        self
+    }
+
+    /// Query parameters may repeat, e.g. for array values.
+    pub fn query_param(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.query.push((name, value.into()));
+        self
+    }
+
+    /// Replaces every value of the query parameter `name` with `value`.
+    pub(crate) fn replace_query(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.query.retain(|(n, _)| *n != name);
+        self.query_param(name, value)
+    }
+
+    pub(crate) fn query_value(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn header_param(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.retain(|(n, _)| *n != name);
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Serialize `body` as the JSON request payload.
+    pub fn json_body<B: Serialize>(mut self, body: B) -> Result<Self> {
+        self.body = Some(serde_json::to_vec(&body)?);
+        Ok(self)
+    }
+
+    /// Like [`json_body`](Self::json_body), but a serialization failure is
+    /// returned by `send` instead.
+    pub fn body<B: Serialize>(mut self, body: B) -> Self {
+        match serde_json::to_vec(&body) {
+            Ok(body) => self.body = Some(body),
+            Err(e) => self.body_error = Some(e.to_string()),
+        }
+        self
+    }
+
+    /// Check `body` against the constraints of its schema; a violation is
+    /// returned by `send` instead of making the request.
+    #[cfg(feature = "validation")]
+    pub fn validate_body<B: crate::validation::Validate>(mut self, body: &B) -> Self {
+        self.invalid = body.validate().err();
+        self
+    }
+
+    fn check_required(&self) -> Result<()> {
+        let missing = |required: &[&'static str], set: &[(&'static str, String)], location| {
+            required
+                .iter()
+                .find(|name| !set.iter().any(|(n, _)| n == *name))
+                .map(|name| Error::MissingParameter { location, name })
+        };
+
+        if let Some(err) = missing(&self.required_path, &self.path_params, "path")
+            .or_else(|| missing(&self.required_query, &self.query, "query"))
+            .or_else(|| missing(&self.required_headers, &self.headers, "header"))
+        {
+            return Err(err);
+        }
+
+        if let Some(msg) = &self.body_error {
+            return Err(Error::Serialization(serde::ser::Error::custom(msg)));
+        }
+
+        if self.body_required && self.body.is_none() {
+            return Err(Error::MissingBody);
+        }
+
+        #[cfg(feature = "validation")]
+        if let Some(err) = &self.invalid {
+            return Err(Error::Validation(err.clone()));
+        }
+
+        Ok(())
+    }
+
+    fn url(&self) -> Result<Url> {
+        let mut path = self.path.to_string();
+        for (name, value) in &self.path_params {
+            let encoded = utf8_percent_encode(value, PATH_SEGMENT).to_string();
+            path = path.replace(&format!("{{{name}}}"), &encoded);
+        }
+
+        let mut url = self.client.join_url(&path)?;
+        if !self.query.is_empty() {
+            let mut pairs = url.query_pairs_mut();
+            for (name, value) in &self.query {
+                pairs.append_pair(name, value);
+            }
+        }
+
+        Ok(url)
+    }
+
+    fn request(self) -> Result<Request> {
+        self.check_required()?;
+
+        let url = self.url()?;
+        let mut req = Request::new(self.method, url);
+
+        for (name, value) in self.headers {
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::InvalidHeader(name.to_string()))?;
+            let value = HeaderValue::from_str(&value)
+                .map_err(|_| Error::InvalidHeader(name.to_string()))?;
+            req.headers_mut().append(header, value);
+        }
+
+        if let Some(body) = self.body {
+            req.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            *req.body_mut() = Some(body.into());
+        }
+
+        self.client.authorize(&mut req, self.security)?;
+
+        Ok(req)
+    }
+
+    /// Sends the request, decoding the response into `U` instead of `T`.
+    pub(crate) async fn send_as<U: DeserializeOwned>(self) -> ApiResult<ApiResponse<U>> {
+        let client = self.client;
+        let retryable = self.retry_safe || is_idempotent(&self.method);
+        let empty_response = self.empty_response;
+        let req = self.request()?;
+        let resp = client.send_retrying(req, retryable).await?;
+
+        let status = resp.status();
+        let rate_limit = RateLimitInfo::from_headers(resp.headers());
+        let value = decode(resp, empty_response).await?;
+        Ok(ApiResponse {
+            value,
+            status,
+            rate_limit,
+        })
+    }
+}
+
+impl<T: DeserializeOwned> ApiRequestBuilder<'_, T> {
+    /// Validate the required parameters, execute the request and decode the
+    /// response body.
+    pub async fn send(self) -> ApiResult<T> {
+        Ok(self.send_with_info().await?.value)
+    }
+
+    /// Like [`send`](Self::send), keeping the status and rate limit of the
+    /// response.
+    pub async fn send_with_info(self) -> ApiResult<ApiResponse<T>> {
+        self.send_as().await
+    }
+}
+
+async fn decode<T: DeserializeOwned>(resp: Response, empty_response: bool) -> Result<T> {
+    if !resp.status().is_success() {
+        return Err(Error::Api(Box::new(ApiError::from_response(resp).await)));
+    }
+
+    let bytes = resp.bytes().await?;
+    // An empty body decodes like `null`, e.g. into `()` or `Option<T>`.
+    if empty_response || bytes.iter().all(u8::is_ascii_whitespace) {
+        return Ok(serde_json::from_value(serde_json::Value::Null)?);
+    }
+    Ok(serde_json::from_slice(&bytes)?)
+}
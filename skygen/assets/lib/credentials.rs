@@ -0,0 +1,285 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sources the client loads its [`Credentials`] from, so that the secrets
+//! used by the provider's own CLI are picked up without extra code.
+
+use crate::auth::Credentials;
+use std::fmt::Debug;
+
+/// Looks up the credentials of a security scheme.
+pub trait CredentialProvider: Debug + Send + Sync {
+    /// The credentials for `scheme`, or `None` when this provider has none.
+    fn credentials(&self, scheme: &str) -> Option<Credentials>;
+}
+
+/// Reads secrets from environment variables, by default the ones configured
+/// when this crate was generated.
+#[derive(Debug, Clone)]
+pub struct EnvProvider {
+    vars: Vec<(String, String)>,
+}
+
+impl EnvProvider {
+    /// Read the secret of `scheme` from `var` instead.
+    pub fn var(mut self, scheme: impl Into<String>, var: impl Into<String>) -> Self {
+        let scheme = scheme.into();
+        self.vars.retain(|(s, _)| *s != scheme);
+        self.vars.push((scheme, var.into()));
+        self
+    }
+}
+
+impl Default for EnvProvider {
+    fn default() -> Self {
+        Self {
+            vars: crate::auth::ENV_VARS
+                .iter()
+                .map(|(scheme, var)| (scheme.to_string(), var.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl CredentialProvider for EnvProvider {
+    fn credentials(&self, scheme: &str) -> Option<Credentials> {
+        let (_, var) = self.vars.iter().find(|(s, _)| s == scheme)?;
+        let secret = std::env::var(var).ok().filter(|s| !s.is_empty())?;
+        Credentials::from_secret(scheme, secret)
+    }
+}
+
+/// A config file of the provider's CLI holding the secret of a scheme, e.g.
+/// `~/.config/hcloud/cli.toml`.
+#[cfg(feature = "profile")]
+#[derive(Debug, Clone)]
+pub struct ProfileFile {
+    pub scheme: String,
+    /// YAML, TOML or JSON file, `~` standing for the home directory.
+    pub path: String,
+    /// Environment variable overriding `path`.
+    pub path_env: Option<String>,
+    /// Key of the secret in the selected profile or at the top level.
+    pub key: String,
+    /// Key of the profiles, a table keyed by name or an array of tables
+    /// with a `name`.
+    pub profiles: Option<String>,
+    /// Top level key naming the active profile.
+    pub active: Option<String>,
+    /// Environment variable overriding the active profile.
+    pub profile_env: Option<String>,
+}
+
+/// Reads secrets from the provider's CLI config files, by default the ones
+/// configured when this crate was generated.
+#[cfg(feature = "profile")]
+#[derive(Debug, Clone)]
+pub struct ProfileProvider {
+    files: Vec<ProfileFile>,
+    profile: Option<String>,
+}
+
+#[cfg(feature = "profile")]
+impl ProfileProvider {
+    /// Also look in `file`, after the files added before.
+    pub fn file(mut self, file: ProfileFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    /// Use the profile `name` instead of the active one.
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    fn secret(&self, file: &ProfileFile) -> Option<String> {
+        use serde_json::Value;
+
+        let path = match file.path_env.as_deref().and_then(|v| std::env::var(v).ok()) {
+            Some(path) => std::path::PathBuf::from(path),
+            None => expand_home(&file.path)?,
+        };
+        let text = std::fs::read_to_string(&path).ok()?;
+        let parsed = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str::<Value>(&text).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()),
+            _ => serde_yaml::from_str::<Value>(&text).map_err(|e| e.to_string()),
+        };
+        let doc = match parsed {
+            Ok(doc) => doc,
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                {
+                    tracing::debug!(path = %path.display(), error = %_e, "skipping unreadable profile");
+                }
+                return None;
+            }
+        };
+
+        let profile = self
+            .profile
+            .clone()
+            .or_else(|| std::env::var(file.profile_env.as_deref()?).ok())
+            .or_else(|| Some(doc.get(file.active.as_deref()?)?.as_str()?.to_string()));
+        let section = profile
+            .zip(file.profiles.as_deref())
+            .and_then(|(name, profiles)| match doc.get(profiles)? {
+                Value::Object(map) => map.get(&name),
+                Value::Array(list) => list
+                    .iter()
+                    .find(|p| p.get("name").and_then(Value::as_str) == Some(name.as_str())),
+                _ => None,
+            });
+
+        // Profiles may map straight to the secret, as doctl's `auth-contexts`.
+        section
+            .and_then(|s| s.as_str().or_else(|| s.get(&file.key)?.as_str()))
+            .or_else(|| doc.get(&file.key)?.as_str())
+            .map(str::to_string)
+    }
+}
+
+#[cfg(feature = "profile")]
+impl Default for ProfileProvider {
+    fn default() -> Self {
+        Self {
+            files: crate::auth::profile_files(),
+            profile: None,
+        }
+    }
+}
+
+#[cfg(feature = "profile")]
+impl CredentialProvider for ProfileProvider {
+    fn credentials(&self, scheme: &str) -> Option<Credentials> {
+        self.files
+            .iter()
+            .filter(|f| f.scheme == scheme)
+            .find_map(|f| self.secret(f))
+            .and_then(|secret| Credentials::from_secret(scheme, secret))
+    }
+}
+
+#[cfg(feature = "profile")]
+fn expand_home(path: &str) -> Option<std::path::PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => {
+            let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+            Some(std::path::Path::new(&home).join(rest))
+        }
+        None => Some(path.into()),
+    }
+}
+
+/// Asks each provider in turn and returns the first credentials found.
+#[derive(Debug, Default)]
+pub struct ChainProvider {
+    providers: Vec<Box<dyn CredentialProvider>>,
+}
+
+impl ChainProvider {
+    /// Ask `provider` after the ones added before.
+    pub fn with(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Environment variables first, then the CLI config files when the
+    /// `profile` feature is enabled.
+    pub fn standard() -> Self {
+        let chain = Self::default().with(EnvProvider::default());
+        #[cfg(feature = "profile")]
+        let chain = chain.with(ProfileProvider::default());
+        chain
+    }
+}
+
+impl CredentialProvider for ChainProvider {
+    fn credentials(&self, scheme: &str) -> Option<Credentials> {
+        self.providers.iter().find_map(|p| p.credentials(scheme))
+    }
+}
+
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+active_context = "default"
+token = "top-level"
+
+[contexts.default]
+token = "default-token"
+
+[contexts.staging]
+token = "staging-token"
+"#;
+
+    fn profile_file(name: &str, contents: &str) -> ProfileFile {
+        let path = std::env::temp_dir().join(format!("profile-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        ProfileFile {
+            scheme: "token".into(),
+            path: path.to_string_lossy().into_owned(),
+            path_env: None,
+            key: "token".into(),
+            profiles: Some("contexts".into()),
+            active: Some("active_context".into()),
+            profile_env: None,
+        }
+    }
+
+    fn provider() -> ProfileProvider {
+        ProfileProvider {
+            files: Vec::new(),
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn reads_the_active_profile() {
+        let file = profile_file("active.toml", CONFIG);
+        assert_eq!(provider().secret(&file).as_deref(), Some("default-token"));
+    }
+
+    #[test]
+    fn selected_profile_overrides_the_active_one() {
+        let file = profile_file("selected.toml", CONFIG);
+        let provider = provider().profile("staging");
+        assert_eq!(provider.secret(&file).as_deref(), Some("staging-token"));
+
+        let missing = self::provider().profile("missing");
+        assert_eq!(missing.secret(&file).as_deref(), Some("top-level"));
+    }
+
+    #[test]
+    fn reads_profile_lists_and_bare_secrets() {
+        let list = profile_file(
+            "list.yaml",
+            "active_context: b\ncontexts:\n  - name: a\n    token: a-token\n  - name: b\n    token: b-token\n",
+        );
+        assert_eq!(provider().secret(&list).as_deref(), Some("b-token"));
+
+        // doctl maps each context straight to its token.
+        let bare = profile_file(
+            "bare.json",
+            r#"{ "active_context": "work", "contexts": { "work": "work-token" } }"#,
+        );
+        assert_eq!(provider().secret(&bare).as_deref(), Some("work-token"));
+    }
+}
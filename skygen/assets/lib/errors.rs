@@ -23,10 +23,64 @@ pub enum Error {
     Transport(Box<dyn std::error::Error + Send + Sync>),
 
     /// Non-success responses from the API.
-    Api(ApiError),
+    Api(Box<ApiError>),
 
     /// JSON (de)serialization.
     Serialization(serde_json::Error),
+
+    /// A required path, query or header parameter was never set.
+    MissingParameter {
+        location: &'static str,
+        name: &'static str,
+    },
+
+    /// The operation requires a request body and none was set.
+    MissingBody,
+
+    /// The client has no credentials for any of the alternative sets of
+    /// security schemes the operation accepts.
+    MissingCredentials(&'static [&'static [&'static str]]),
+
+    /// A header name or value that cannot be sent over HTTP.
+    InvalidHeader(String),
+
+    /// The request body violates a constraint of its schema.
+    #[cfg(feature = "validation")]
+    Validation(crate::validation::ValidationError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "transport error: {e}"),
+            Self::Api(e) => write!(f, "api error: {e}"),
+            Self::Serialization(e) => write!(f, "serialization error: {e}"),
+            Self::MissingParameter { location, name } => {
+                write!(f, "missing required {location} parameter: {name}")
+            }
+            Self::MissingBody => write!(f, "missing required request body"),
+            Self::MissingCredentials(security) => {
+                let alternatives: Vec<String> = security.iter().map(|s| s.join(" and ")).collect();
+                write!(f, "missing credentials for {}", alternatives.join(", or "))
+            }
+            Self::InvalidHeader(name) => write!(f, "invalid value for header: {name}"),
+            #[cfg(feature = "validation")]
+            Self::Validation(e) => write!(f, "invalid request body: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(e) => Some(e.as_ref()),
+            Self::Api(e) => Some(e.as_ref()),
+            Self::Serialization(e) => Some(e),
+            #[cfg(feature = "validation")]
+            Self::Validation(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<serde_json::Error> for Error {
@@ -36,8 +90,9 @@ impl From<serde_json::Error> for Error {
 }
 
 impl From<reqwest::Error> for Error {
+    /// Drops the request URL, whose query may carry an API key.
     fn from(e: reqwest::Error) -> Self {
-        Self::Transport(Box::new(e))
+        Self::Transport(Box::new(e.without_url()))
     }
 }
 
@@ -45,10 +100,38 @@ impl From<reqwest::Error> for Error {
 pub struct ApiError {
     pub status: reqwest::StatusCode,
     pub request_id: Option<String>,
+    pub rate_limit: Option<crate::rate_limit::RateLimitInfo>,
     pub content_type: Option<String>,
     pub body: Vec<u8>,
 }
 
+impl ApiError {
+    /// Capture the status, identifying headers and raw body of a failed
+    /// response.
+    pub async fn from_response(resp: reqwest::Response) -> Self {
+        let header = |name: &str| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let status = resp.status();
+        let request_id = header("x-request-id");
+        let rate_limit = crate::rate_limit::RateLimitInfo::from_headers(resp.headers());
+        let content_type = header(reqwest::header::CONTENT_TYPE.as_str());
+        let body = resp.bytes().await.map(|b| b.to_vec()).unwrap_or_default();
+
+        Self {
+            status,
+            request_id,
+            rate_limit,
+            content_type,
+            body,
+        }
+    }
+}
+
 impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "status {}", self.status)?;
@@ -0,0 +1,45 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serde helpers for optional fields that may also be `null`.
+//!
+//! Such fields are generated as `Option<Option<T>>`: `None` leaves the field
+//! out of the payload, `Some(None)` sends an explicit `null`, e.g. to clear a
+//! value in a PATCH request, and `Some(Some(value))` sends the value.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<T, S>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match value {
+        Some(inner) => inner.serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Only called for fields present in the payload; absent fields fall back
+/// to `None` through `#[serde(default)]`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
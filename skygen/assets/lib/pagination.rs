@@ -0,0 +1,235 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Following the pages of list operations, see the `stream` and
+//! `collect_all` methods of their builders.
+
+use crate::client::ApiRequestBuilder;
+use crate::errors::Result;
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Query parameter selecting the page to list.
+const PAGE: &str = "page";
+
+/// Where a list response says whether another page follows. Pages are
+/// requested by number through the `page` query parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pagination {
+    /// `links.pages.next` links to the next page (DigitalOcean).
+    NextLink,
+    /// `meta.pagination.next_page` numbers the next page (Hetzner).
+    NextPage,
+    /// Pages follow until `total_count` items were listed, or until an empty
+    /// page without it (Scaleway).
+    TotalCount,
+    /// `result_info.page` counts up to `result_info.total_pages` (Cloudflare).
+    ResultInfo,
+}
+
+impl Pagination {
+    /// The number of the page after `page`, `None` on the last one. `listed`
+    /// counts the items of every page so far, this one included.
+    fn next_page(self, body: &Value, page: u64, listed: u64) -> Option<u64> {
+        match self {
+            Self::NextLink => {
+                let next = body.pointer("/links/pages/next")?.as_str()?;
+                // The link may be relative to the API.
+                let url = Url::parse("http://localhost/").ok()?.join(next).ok()?;
+                let (_, value) = url.query_pairs().find(|(name, _)| name == PAGE)?;
+                value.parse().ok()
+            }
+            Self::NextPage => body.pointer("/meta/pagination/next_page")?.as_u64(),
+            Self::TotalCount => match body.get("total_count").and_then(Value::as_u64) {
+                Some(total) => (listed < total).then_some(page + 1),
+                None => Some(page + 1),
+            },
+            Self::ResultInfo => {
+                let info = body.get("result_info")?;
+                let total = info.get("total_pages")?.as_u64()?;
+                let page = info.get("page").and_then(Value::as_u64).unwrap_or(page);
+                (page < total).then_some(page + 1)
+            }
+        }
+    }
+}
+
+/// Streams the items under `items` on every page listed by `builder`,
+/// starting from its `page` parameter if set. The next page is only
+/// requested once the items of the previous one are consumed, and listing
+/// stops at an empty page or one that does not move forward. The stream is
+/// boxed so that it can be polled without pinning it first.
+pub fn paginate<'a, T, R>(
+    builder: ApiRequestBuilder<'a, R>,
+    pagination: Pagination,
+    items: &'static str,
+) -> impl Stream<Item = Result<T>> + Unpin + 'a
+where
+    T: DeserializeOwned + 'a,
+    R: Clone + 'a,
+{
+    let page = builder
+        .query_value(PAGE)
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(1);
+
+    let pages = stream::try_unfold(Some((builder, page, 0)), move |cursor| {
+        list_page(cursor, pagination, items)
+    });
+    Box::pin(
+        pages
+            .map_ok(|values| stream::iter(values.into_iter().map(Ok)))
+            .try_flatten(),
+    )
+}
+
+/// The request for a page, its number and the count of items listed
+/// before it.
+type Cursor<'a, R> = Option<(ApiRequestBuilder<'a, R>, u64, u64)>;
+
+/// Lists the page at `cursor`, returning its items and the cursor of the
+/// page after it.
+async fn list_page<'a, T, R>(
+    cursor: Cursor<'a, R>,
+    pagination: Pagination,
+    items: &str,
+) -> Result<Option<(Vec<T>, Cursor<'a, R>)>>
+where
+    T: DeserializeOwned,
+    R: Clone,
+{
+    let Some((builder, page, listed)) = cursor else {
+        return Ok(None);
+    };
+    let mut body: Value = builder.clone().send_as().await?.value;
+    let values: Vec<T> = match body.get_mut(items).map(Value::take) {
+        Some(Value::Null) | None => Vec::new(),
+        Some(values) => serde_json::from_value(values)?,
+    };
+
+    let listed = listed + values.len() as u64;
+    let next = pagination
+        .next_page(&body, page, listed)
+        .filter(|next| !values.is_empty() && *next > page)
+        .map(|next| (builder.replace_query(PAGE, next.to_string()), next, listed));
+    Ok(Some((values, next)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+    use crate::credentials::ChainProvider;
+    use reqwest::Method;
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves `pages[n - 1]` for `?page=n`, recording the pages requested.
+    fn serve(pages: Vec<Value>) -> (Url, Arc<Mutex<Vec<u64>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let log = requested.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut head = Vec::new();
+                let mut byte = [0];
+                while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+                    head.push(byte[0]);
+                }
+                let head = String::from_utf8(head).unwrap();
+                let target = head.split(' ').nth(1).unwrap();
+                let url = Url::parse("http://localhost/")
+                    .unwrap()
+                    .join(target)
+                    .unwrap();
+                let page = url
+                    .query_pairs()
+                    .find(|(name, _)| name == PAGE)
+                    .map_or(1, |(_, value)| value.parse().unwrap());
+                log.lock().unwrap().push(page);
+
+                let body = pages[page as usize - 1].to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (base, requested)
+    }
+
+    async fn list(pages: Vec<Value>, pagination: Pagination) -> (Vec<u64>, Vec<u64>) {
+        let (base, requested) = serve(pages);
+        let client = Client::builder()
+            .base_url(base)
+            .credential_provider(ChainProvider::default())
+            .build()
+            .unwrap();
+        let builder = ApiRequestBuilder::<Value>::new(&client, Method::GET, "/items");
+        let items = paginate::<u64, _>(builder, pagination, "items")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let requested = requested.lock().unwrap().clone();
+        (items, requested)
+    }
+
+    #[tokio::test]
+    async fn stops_after_the_last_page() {
+        let pages = vec![
+            json!({ "items": [1, 2], "meta": { "pagination": { "next_page": 2 } } }),
+            json!({ "items": [3], "meta": { "pagination": { "next_page": null } } }),
+        ];
+        assert_eq!(
+            list(pages, Pagination::NextPage).await,
+            (vec![1, 2, 3], vec![1, 2])
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_at_an_empty_page() {
+        let pages = vec![
+            json!({ "items": [1, 2] }),
+            json!({ "items": [] }),
+            json!({ "items": [3] }),
+        ];
+        assert_eq!(
+            list(pages, Pagination::TotalCount).await,
+            (vec![1, 2], vec![1, 2])
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_once_the_total_is_listed() {
+        let pages = vec![
+            json!({ "items": [1, 2], "total_count": 3 }),
+            json!({ "items": [3], "total_count": 3 }),
+            json!({ "items": [4], "total_count": 3 }),
+        ];
+        assert_eq!(
+            list(pages, Pagination::TotalCount).await,
+            (vec![1, 2, 3], vec![1, 2])
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The request quota reported by the API and the optional client-side
+//! throttle keeping within it.
+
+use reqwest::header::HeaderMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The request quota reported by a response. DigitalOcean and Hetzner send
+/// `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset`,
+/// Cloudflare the structured `RateLimit` and `RateLimit-Policy` headers and
+/// others `X-RateLimit-*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Requests allowed per window.
+    pub limit: Option<u64>,
+    /// Requests left in the current window.
+    pub remaining: Option<u64>,
+    /// When the quota is restored.
+    pub reset: Option<SystemTime>,
+}
+
+impl RateLimitInfo {
+    /// Parses the rate limit headers of a response, `None` without any.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let text = |name: &str| Some(headers.get(name)?.to_str().ok()?.trim());
+        let number = |suffix: &str| {
+            [
+                format!("ratelimit-{suffix}"),
+                format!("x-ratelimit-{suffix}"),
+            ]
+            .iter()
+            .find_map(|name| text(name)?.parse::<u64>().ok())
+        };
+
+        let mut info = Self {
+            limit: number("limit"),
+            remaining: number("remaining"),
+            reset: number("reset").map(reset_time),
+        };
+        // Cloudflare: `RateLimit: "default";r=50;t=30` and
+        // `RateLimit-Policy: "default";q=100;w=60`.
+        if let Some(value) = text("ratelimit") {
+            info.remaining = info.remaining.or_else(|| param(value, "r"));
+            info.reset = info.reset.or_else(|| param(value, "t").map(reset_time));
+        }
+        if let Some(value) = text("ratelimit-policy") {
+            info.limit = info.limit.or_else(|| param(value, "q"));
+        }
+
+        let found = info.limit.is_some() || info.remaining.is_some() || info.reset.is_some();
+        found.then_some(info)
+    }
+
+    /// Time left until the quota is restored.
+    pub fn reset_after(&self) -> Option<Duration> {
+        self.reset?.duration_since(SystemTime::now()).ok()
+    }
+}
+
+/// Reset headers hold either a Unix timestamp or the seconds left.
+fn reset_time(value: u64) -> SystemTime {
+    if value < 1_000_000_000 {
+        SystemTime::now() + Duration::from_secs(value)
+    } else {
+        UNIX_EPOCH + Duration::from_secs(value)
+    }
+}
+
+/// The value of `key` in the first item of a structured header, e.g. `r`
+/// in `"default";r=50;t=30`.
+fn param(value: &str, key: &str) -> Option<u64> {
+    value.split(',').next()?.split(';').skip(1).find_map(|p| {
+        let (k, v) = p.split_once('=')?;
+        if k.trim() == key {
+            v.trim().parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+/// Spaces requests out once the remaining quota runs low, so that what is
+/// left lasts until the quota resets instead of ending in `429`s.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    low_watermark: f64,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self { low_watermark: 0.1 }
+    }
+}
+
+impl Throttle {
+    /// Fraction of the limit below which requests are spaced out, 10% by
+    /// default.
+    pub fn low_watermark(mut self, fraction: f64) -> Self {
+        self.low_watermark = fraction;
+        self
+    }
+}
+
+/// The last quota reported by the API, shared by the clones of a client.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimitState(Arc<Mutex<Bucket>>);
+
+#[derive(Debug, Default)]
+struct Bucket {
+    info: Option<RateLimitInfo>,
+    /// Earliest time the next throttled request may go out.
+    next_slot: Option<Instant>,
+}
+
+impl RateLimitState {
+    pub(crate) fn current(&self) -> Option<RateLimitInfo> {
+        self.lock().info
+    }
+
+    pub(crate) fn update(&self, info: RateLimitInfo) {
+        self.lock().info = Some(info);
+    }
+
+    /// How long to wait before sending a request, which is counted against
+    /// the remaining quota until the next response reports it again.
+    pub(crate) fn acquire(&self, throttle: &Throttle) -> Duration {
+        let mut bucket = self.lock();
+        let Some(info) = bucket.info.as_mut() else {
+            return Duration::ZERO;
+        };
+        let (Some(remaining), Some(window)) = (info.remaining, info.reset_after()) else {
+            return Duration::ZERO;
+        };
+        info.remaining = Some(remaining.saturating_sub(1));
+
+        let low = info
+            .limit
+            .map_or(0, |limit| (limit as f64 * throttle.low_watermark) as u64);
+        if remaining > low {
+            return Duration::ZERO;
+        }
+
+        // Refill at the pace that spreads what is left until the reset.
+        let spacing = window / u32::try_from(remaining + 1).unwrap_or(u32::MAX);
+        let now = Instant::now();
+        let slot = bucket.next_slot.map_or(now, |next| next.max(now));
+        bucket.next_slot = Some(slot + spacing);
+        slot - now
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Bucket> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    pub(crate) fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn info(limit: u64, remaining: u64, reset_in: u64) -> RateLimitInfo {
+        RateLimitInfo {
+            limit: Some(limit),
+            remaining: Some(remaining),
+            reset: Some(SystemTime::now() + Duration::from_secs(reset_in)),
+        }
+    }
+
+    #[test]
+    fn parses_ratelimit_headers() {
+        let info = RateLimitInfo::from_headers(&headers(&[
+            ("ratelimit-limit", "5000"),
+            ("ratelimit-remaining", " 4999 "),
+            ("ratelimit-reset", "1700000000"),
+        ]))
+        .unwrap();
+        assert_eq!(info.limit, Some(5000));
+        assert_eq!(info.remaining, Some(4999));
+        assert_eq!(
+            info.reset,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+    }
+
+    #[test]
+    fn parses_x_ratelimit_headers_with_relative_reset() {
+        let info = RateLimitInfo::from_headers(&headers(&[
+            ("x-ratelimit-remaining", "3"),
+            ("x-ratelimit-reset", "30"),
+        ]))
+        .unwrap();
+        assert_eq!(info.limit, None);
+        assert_eq!(info.remaining, Some(3));
+        let reset = info.reset_after().unwrap();
+        assert!(reset > Duration::from_secs(25) && reset <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parses_structured_headers() {
+        let info = RateLimitInfo::from_headers(&headers(&[
+            ("ratelimit", "\"default\";r=50;t=30, \"burst\";r=1;t=1"),
+            ("ratelimit-policy", "\"default\";q=100;w=60"),
+        ]))
+        .unwrap();
+        assert_eq!(info.limit, Some(100));
+        assert_eq!(info.remaining, Some(50));
+        assert!(info.reset_after().is_some());
+    }
+
+    #[test]
+    fn ignores_responses_without_quota() {
+        assert_eq!(RateLimitInfo::from_headers(&HeaderMap::new()), None);
+        let invalid = headers(&[("ratelimit-remaining", "many")]);
+        assert_eq!(RateLimitInfo::from_headers(&invalid), None);
+    }
+
+    #[test]
+    fn throttles_below_the_low_watermark() {
+        let state = RateLimitState::default();
+        let throttle = Throttle::default();
+        assert_eq!(state.acquire(&throttle), Duration::ZERO);
+
+        state.update(info(100, 50, 60));
+        assert_eq!(state.acquire(&throttle), Duration::ZERO);
+        assert_eq!(state.current().unwrap().remaining, Some(49));
+
+        state.update(info(100, 5, 60));
+        assert_eq!(state.acquire(&throttle), Duration::ZERO);
+        // Requests are spread over the window by what is left: 60s / 6 after
+        // the first one, then 60s / 5 more.
+        let first = state.acquire(&throttle);
+        let second = state.acquire(&throttle);
+        assert!(first > Duration::from_secs(9) && first <= Duration::from_secs(10));
+        assert!(second > first + Duration::from_secs(11));
+    }
+}
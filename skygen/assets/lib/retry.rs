@@ -0,0 +1,292 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! When and how long [`Client::send`](crate::Client::send) waits before
+//! sending a failed request again.
+
+use crate::rate_limit::RateLimitInfo;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Retries requests that failed to connect or were answered with `429`,
+/// `502`, `503` or `504`, waiting exponentially longer between attempts.
+/// Only idempotent methods are retried, unless the operation is marked safe.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request once.
+    pub fn none() -> Self {
+        Self::default().max_retries(0)
+    }
+
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every following one.
+    pub fn initial_backoff(mut self, delay: Duration) -> Self {
+        self.initial_backoff = delay;
+        self
+    }
+
+    /// Upper bound of every delay, including the ones asked for by the
+    /// server.
+    pub fn max_backoff(mut self, delay: Duration) -> Self {
+        self.max_backoff = delay;
+        self
+    }
+
+    pub(crate) fn allows(&self, attempt: u32) -> bool {
+        attempt < self.max_retries
+    }
+
+    /// Delay before retry number `attempt`, counted from 0: what the server
+    /// asked for in the status and headers of its response, exponential
+    /// backoff with jitter otherwise.
+    pub(crate) fn delay(
+        &self,
+        attempt: u32,
+        response: Option<(StatusCode, &HeaderMap)>,
+    ) -> Duration {
+        let delay = response
+            .and_then(|(status, headers)| server_delay(status, headers))
+            .unwrap_or_else(|| self.backoff(attempt));
+        delay.min(self.max_backoff)
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        // Half of the delay is random, so clients failing together spread out.
+        let half = delay / 2;
+        half + half.mul_f64(jitter())
+    }
+}
+
+/// Statuses that ask to try again later.
+pub(crate) fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Methods that can be repeated without changing the outcome, RFC 9110.
+pub(crate) fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// The wait asked for by `Retry-After` or, failing that, by the reset of the
+/// rate limit. The reset only matters once the quota is used up: a `503`
+/// that happens to carry rate limit headers is backed off from as usual.
+fn server_delay(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    if let Some(value) = headers.get(RETRY_AFTER).and_then(|v| v.to_str().ok()) {
+        return retry_after(value.trim());
+    }
+    let info = RateLimitInfo::from_headers(headers)?;
+    if status == StatusCode::TOO_MANY_REQUESTS || info.remaining == Some(0) {
+        info.reset_after()
+    } else {
+        None
+    }
+}
+
+/// `Retry-After` holds either the seconds to wait or an HTTP date, RFC 9110.
+fn retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = http_date(value)?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// Parses the IMF-fixdate form of an HTTP date, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT`, the only one senders may use.
+fn http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (_weekday, rest) = value.split_once(", ")?;
+    let mut parts = rest.split(' ');
+    let day: u64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let year: u64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|p| p.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if parts.next()? != "GMT" || parts.next().is_some() || time.next().is_some() {
+        return None;
+    }
+    if year < 1970 || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since the epoch of a proleptic Gregorian date, counting years
+    // from March so the leap day comes last.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let days = 365 * y + y / 4 - y / 100 + y / 400 + (153 * m + 2) / 5 + day - 1 - 719_468;
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// A number in `[0, 1)` that differs between calls.
+fn jitter() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u128(now.as_nanos());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::tests::headers;
+
+    fn in_secs(secs: u64) -> String {
+        let at = SystemTime::now() + Duration::from_secs(secs);
+        at.duration_since(UNIX_EPOCH).unwrap().as_secs().to_string()
+    }
+
+    #[test]
+    fn parses_http_dates() {
+        let at = http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(at, UNIX_EPOCH + Duration::from_secs(784_111_777));
+        let leap = http_date("Tue, 29 Feb 2000 00:00:00 GMT").unwrap();
+        assert_eq!(leap, UNIX_EPOCH + Duration::from_secs(951_782_400));
+
+        for invalid in [
+            "Sun, 06 Nov 1994 08:49:37 UTC",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+            "Sun, 06 Nov 1994 08:49 GMT",
+            "Sun, 32 Nov 1994 08:49:37 GMT",
+        ] {
+            assert_eq!(http_date(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn retry_after_takes_seconds_or_dates() {
+        assert_eq!(retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            retry_after("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after("soon"), None);
+    }
+
+    #[test]
+    fn server_delay_prefers_retry_after() {
+        let reset = in_secs(600);
+        let headers = headers(&[("retry-after", "5"), ("ratelimit-reset", &reset)]);
+        assert_eq!(
+            server_delay(StatusCode::TOO_MANY_REQUESTS, &headers),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn server_delay_waits_for_reset_only_when_exhausted() {
+        let reset = in_secs(60);
+        let limited = headers(&[("ratelimit-remaining", "10"), ("ratelimit-reset", &reset)]);
+        let exhausted = headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", &reset)]);
+        let around = |delay: Option<Duration>| delay.is_some_and(|d| d.as_secs() > 50);
+
+        assert!(around(server_delay(
+            StatusCode::TOO_MANY_REQUESTS,
+            &limited
+        )));
+        assert!(around(server_delay(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &exhausted
+        )));
+        assert_eq!(
+            server_delay(StatusCode::SERVICE_UNAVAILABLE, &limited),
+            None
+        );
+        assert_eq!(
+            server_delay(StatusCode::BAD_GATEWAY, &HeaderMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn delay_is_capped() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(10));
+        let asked = headers(&[("retry-after", "3600")]);
+        assert_eq!(
+            policy.delay(0, Some((StatusCode::TOO_MANY_REQUESTS, &asked))),
+            Duration::from_secs(10)
+        );
+
+        for attempt in 0..8 {
+            let ceiling = Duration::from_secs(1 << attempt).min(Duration::from_secs(10));
+            let delay = policy.delay(attempt, None);
+            assert!(
+                delay >= ceiling / 2 && delay <= ceiling,
+                "{attempt}: {delay:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn retries_idempotent_methods_and_transient_statuses() {
+        assert!(is_idempotent(&Method::PUT));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(RetryPolicy::default().allows(2));
+        assert!(!RetryPolicy::none().allows(0));
+    }
+}
@@ -0,0 +1,165 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Types for formatted strings.
+//!
+//! `date-time` and `date` values use `chrono` or `time`, whichever feature is
+//! enabled (`chrono` wins when both are), and `uuid` values the `uuid` crate.
+//! Without a backend they are kept as plain strings.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::Deref;
+
+#[cfg(feature = "chrono")]
+pub type DateTime = chrono::DateTime<chrono::FixedOffset>;
+#[cfg(feature = "chrono")]
+pub type Date = chrono::NaiveDate;
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub use self::time_backend::{Date, DateTime};
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+pub type DateTime = String;
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+pub type Date = String;
+
+#[cfg(feature = "uuid")]
+pub type Uuid = uuid::Uuid;
+#[cfg(not(feature = "uuid"))]
+pub type Uuid = String;
+
+/// Binary data sent as a base64 string (`format: byte`), holding the
+/// decoded bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Base64(pub Vec<u8>);
+
+impl Deref for Base64 {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Base64 {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Base64> for Vec<u8> {
+    fn from(value: Base64) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Base64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Base64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map(Self).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+mod time_backend {
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::ops::Deref;
+    use time::format_description::well_known::Rfc3339;
+    use time::format_description::BorrowedFormatItem;
+    use time::macros::format_description;
+
+    const DATE_FORMAT: &[BorrowedFormatItem<'static>] = format_description!("[year]-[month]-[day]");
+
+    /// An RFC 3339 timestamp (`format: date-time`).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct DateTime(pub time::OffsetDateTime);
+
+    impl Deref for DateTime {
+        type Target = time::OffsetDateTime;
+
+        fn deref(&self) -> &time::OffsetDateTime {
+            &self.0
+        }
+    }
+
+    impl From<time::OffsetDateTime> for DateTime {
+        fn from(value: time::OffsetDateTime) -> Self {
+            Self(value)
+        }
+    }
+
+    impl Serialize for DateTime {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let formatted = self.0.format(&Rfc3339).map_err(S::Error::custom)?;
+            serializer.serialize_str(&formatted)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for DateTime {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let raw = String::deserialize(deserializer)?;
+            time::OffsetDateTime::parse(&raw, &Rfc3339)
+                .map(Self)
+                .map_err(D::Error::custom)
+        }
+    }
+
+    /// A calendar date such as `2025-01-31` (`format: date`).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Date(pub time::Date);
+
+    impl Deref for Date {
+        type Target = time::Date;
+
+        fn deref(&self) -> &time::Date {
+            &self.0
+        }
+    }
+
+    impl From<time::Date> for Date {
+        fn from(value: time::Date) -> Self {
+            Self(value)
+        }
+    }
+
+    impl Serialize for Date {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let formatted = self.0.format(DATE_FORMAT).map_err(S::Error::custom)?;
+            serializer.serialize_str(&formatted)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Date {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let raw = String::deserialize(deserializer)?;
+            time::Date::parse(&raw, DATE_FORMAT)
+                .map(Self)
+                .map_err(D::Error::custom)
+        }
+    }
+}
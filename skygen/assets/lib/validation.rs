@@ -0,0 +1,252 @@
+// Auto-generated by codegen - Skygen
+// https://cloudflavor.io/projects/skygen
+//
+// Copyright 2025 Cloudflavor GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client-side checks of the constraints declared by the spec (`minLength`,
+//! `maximum`, `pattern`, `uniqueItems`, ...), run on request bodies before
+//! they are sent.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::sync::{Mutex, OnceLock};
+
+/// A value that violates a constraint of its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Dotted path of the offending field, e.g. `public_net.ipv4`.
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &str, message: String) -> Self {
+        Self {
+            path: field.to_string(),
+            message,
+        }
+    }
+
+    /// Prefixes the path with the field holding the value that failed.
+    pub fn at(mut self, field: &str) -> Self {
+        self.path = if self.path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{field}.{}", self.path)
+        };
+        self
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Implemented by every generated struct and union.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.as_ref().map_or(Ok(()), T::validate)
+    }
+}
+
+impl<T: Validate> Validate for Box<T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        T::validate(self)
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        for (i, item) in self.iter().enumerate() {
+            item.validate().map_err(|e| e.at(&i.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Validate> Validate for HashMap<String, T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        for (key, value) in self {
+            value.validate().map_err(|e| e.at(key))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Validate> Validate for BTreeMap<String, T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        for (key, value) in self {
+            value.validate().map_err(|e| e.at(key))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn min_length<T: AsRef<str> + ?Sized>(
+    field: &str,
+    value: Option<&T>,
+    min: usize,
+) -> Result<(), ValidationError> {
+    match value.map(|v| v.as_ref().chars().count()) {
+        Some(len) if len < min => Err(ValidationError::new(
+            field,
+            format!("length {len} is shorter than {min}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn max_length<T: AsRef<str> + ?Sized>(
+    field: &str,
+    value: Option<&T>,
+    max: usize,
+) -> Result<(), ValidationError> {
+    match value.map(|v| v.as_ref().chars().count()) {
+        Some(len) if len > max => Err(ValidationError::new(
+            field,
+            format!("length {len} is longer than {max}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Patterns are compiled once and cached for the lifetime of the process.
+pub fn pattern<T: AsRef<str> + ?Sized>(
+    field: &str,
+    value: Option<&T>,
+    pattern: &'static str,
+) -> Result<(), ValidationError> {
+    static CACHE: OnceLock<Mutex<HashMap<&'static str, Regex>>> = OnceLock::new();
+
+    let Some(value) = value else {
+        return Ok(());
+    };
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let regex = match cache.get(pattern) {
+        Some(regex) => regex,
+        None => match Regex::new(pattern) {
+            Ok(regex) => cache.entry(pattern).or_insert(regex),
+            // The generator only keeps patterns the regex crate accepts.
+            Err(_) => return Ok(()),
+        },
+    };
+
+    if regex.is_match(value.as_ref()) {
+        Ok(())
+    } else {
+        Err(ValidationError::new(
+            field,
+            format!("does not match pattern {pattern}"),
+        ))
+    }
+}
+
+pub fn minimum<T: PartialOrd + Display>(
+    field: &str,
+    value: Option<&T>,
+    min: T,
+    exclusive: bool,
+) -> Result<(), ValidationError> {
+    match value {
+        Some(v) if *v < min || (exclusive && *v == min) => Err(ValidationError::new(
+            field,
+            format!(
+                "{v} is less than {}{min}",
+                if exclusive { "or equal to " } else { "" }
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn maximum<T: PartialOrd + Display>(
+    field: &str,
+    value: Option<&T>,
+    max: T,
+    exclusive: bool,
+) -> Result<(), ValidationError> {
+    match value {
+        Some(v) if *v > max || (exclusive && *v == max) => Err(ValidationError::new(
+            field,
+            format!(
+                "{v} is greater than {}{max}",
+                if exclusive { "or equal to " } else { "" }
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn min_items<T>(
+    field: &str,
+    value: Option<&Vec<T>>,
+    min: usize,
+) -> Result<(), ValidationError> {
+    match value.map(Vec::len) {
+        Some(len) if len < min => Err(ValidationError::new(
+            field,
+            format!("{len} items are fewer than {min}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn max_items<T>(
+    field: &str,
+    value: Option<&Vec<T>>,
+    max: usize,
+) -> Result<(), ValidationError> {
+    match value.map(Vec::len) {
+        Some(len) if len > max => Err(ValidationError::new(
+            field,
+            format!("{len} items are more than {max}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Items are compared by their JSON form since generated models do not
+/// implement `PartialEq`.
+pub fn unique_items<T: Serialize>(
+    field: &str,
+    value: Option<&Vec<T>>,
+) -> Result<(), ValidationError> {
+    let Some(items) = value else {
+        return Ok(());
+    };
+    let mut seen = Vec::with_capacity(items.len());
+    for item in items {
+        let json =
+            serde_json::to_value(item).map_err(|e| ValidationError::new(field, e.to_string()))?;
+        if seen.contains(&json) {
+            return Err(ValidationError::new(field, format!("{json} is repeated")));
+        }
+        seen.push(json);
+    }
+    Ok(())
+}
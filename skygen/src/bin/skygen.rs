@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{bail, Context};
-use skygen::generator::project::{bootstrap_lib, format_crate};
+use anyhow::Context;
+use skygen::generator::project::format_crate;
+use skygen::resolver::bundle::{self, BundleMode, OutputFormat};
+use skygen::resolver::resolve::DEFAULT_MAX_DEPTH;
 use structopt::StructOpt;
 use tokio::fs;
 use tracing_subscriber::EnvFilter;
@@ -25,46 +27,54 @@ async fn main() -> anyhow::Result<()> {
     let opts_level = opts.log_level;
     let env_filter = EnvFilter::new(opts_level.as_str());
 
+    // Logs go to stderr, leaving stdout to the bundled spec.
     let subscriber = tracing_subscriber::fmt::Subscriber::builder()
         .with_ansi(true)
         .with_env_filter(env_filter)
-        .with_writer(std::io::stdout)
+        .with_writer(std::io::stderr)
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
 
     match opts.commands {
         skygen::Commands::Generate(args) => {
-            let extension = &args
-                .schema
-                .extension()
-                .and_then(|s| s.to_str())
-                .with_context(|| "failed to parse schema file extension")?;
-
             let c = fs::read_to_string(&args.config).await?;
-            let config: skygen::Config = toml::from_str(c.as_str())?;
-            let d = fs::read_to_string(&args.schema).await?;
-            // NOTE: This surfaces in the DigitalOcean OpenAPI spec where the assigned int
-            // overflows what the deserializer can handle so we clamp it to u64::MAX;
-            // If sanitization becomes a habbit in the other specs, move this to a fn
-            let safe_data = d.replace("18446744073709552000", "18446744073709551615");
-
-            let _schema_json: serde_json::Value = match extension.to_lowercase().as_str() {
-                "yaml" | "yml" => serde_yaml::from_str(safe_data.as_str())
-                    .with_context(|| "failed to parse YAML")?,
-                "json" => serde_json::from_str(safe_data.as_str())
-                    .with_context(|| "failed to parse json")?,
-                _ => bail!("unsupported file extension: {extension}"),
-            };
+            let mut config: skygen::Config = toml::from_str(c.as_str())?;
+            if let Some(depth) = args.max_depth {
+                config.set_max_depth(depth);
+            }
+            let spec = skygen::load_spec(&args.schema, config.max_depth())
+                .with_context(|| format!("failed to load spec {}", args.schema.display()))?;
 
-            // let _spec: openapiv3::OpenAPI = serde_json::from_value(schema_json)
-            //     .with_context(|| "failed to conver into OpenAPIv3 spec")?;
-            bootstrap_lib(&config, &args.output)
+            skygen::generate(&config, &spec, &args.output)
                 .await
-                .with_context(|| "failed to bootstrap library")?;
+                .with_context(|| "failed to generate library")?;
 
-            format_crate(&args.output.as_path())
+            format_crate(args.output.as_path())
                 .with_context(|| "failed to format the generated crate")?;
         }
+        skygen::Commands::Bundle(args) => {
+            let mode = if args.dereference {
+                BundleMode::Dereferenced
+            } else {
+                BundleMode::Bundled
+            };
+            let format = args
+                .format
+                .or_else(|| args.output.as_deref().and_then(OutputFormat::from_path))
+                .unwrap_or(OutputFormat::Yaml);
+
+            let max_depth = args.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+            let doc = bundle::bundle(&args.schema, mode, max_depth)
+                .with_context(|| format!("failed to bundle spec {}", args.schema.display()))?;
+            let data = bundle::render(&doc, format)?;
+
+            match &args.output {
+                Some(path) => fs::write(path, data)
+                    .await
+                    .with_context(|| format!("failed to write {}", path.display()))?,
+                None => print!("{data}"),
+            }
+        }
     }
 
     Ok(())
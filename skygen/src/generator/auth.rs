@@ -0,0 +1,263 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::naming::{unique_ident, variant_ident};
+use crate::Config;
+use anyhow::{Context, Result};
+use openapiv3::{APIKeyLocation, OpenAPI, Operation, ReferenceOr, SecurityScheme};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tera::{Context as TeraContext, Tera};
+use tokio::fs;
+
+/// Where the generated providers look for the secret of a security scheme,
+/// configured under `[credentials.<scheme>]`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CredentialSource {
+    /// Environment variable holding the secret, e.g. `HCLOUD_TOKEN`.
+    pub env: Option<String>,
+    pub profile: Option<ProfileSource>,
+}
+
+/// A config file of the provider's CLI holding the secret.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileSource {
+    /// YAML, TOML or JSON file, `~` standing for the home directory.
+    pub path: String,
+    /// Environment variable overriding `path`.
+    pub path_env: Option<String>,
+    /// Key of the secret in the selected profile or at the top level.
+    pub key: String,
+    /// Key of the profiles, a table keyed by name or an array of tables with
+    /// a `name`.
+    pub profiles: Option<String>,
+    /// Top level key naming the active profile.
+    pub active: Option<String>,
+    /// Environment variable overriding the active profile.
+    pub profile_env: Option<String>,
+}
+
+/// A `Credentials` variant generated for one entry in
+/// `components.securitySchemes`.
+#[derive(Debug, Serialize)]
+pub struct AuthScheme {
+    /// The scheme's key in the spec, as referenced by `security`.
+    pub name: String,
+    pub variant: String,
+    /// `bearer`, `basic`, `header` or `query`.
+    pub kind: &'static str,
+    /// Header or query parameter carrying an API key.
+    pub key: Option<String>,
+    #[serde(flatten)]
+    pub source: CredentialSource,
+}
+
+/// Renders `src/auth.rs` with the `Credentials` accepted by the client and
+/// the sources the providers load them from.
+pub async fn generate_auth(
+    tera: &Tera,
+    config: &Config,
+    spec: &OpenAPI,
+    out_dir: impl AsRef<Path>,
+) -> Result<()> {
+    let mut schemes = auth_schemes(spec);
+    for (name, source) in config.credentials() {
+        match schemes.iter_mut().find(|s| s.name == *name) {
+            Some(scheme) => scheme.source = source.clone(),
+            None => tracing::warn!(
+                name,
+                "credentials configured for an unknown security scheme"
+            ),
+        }
+    }
+    tracing::debug!(count = schemes.len(), "generating credentials");
+
+    let mut ctx = TeraContext::new();
+    ctx.insert("schemes", &schemes);
+    let data = tera
+        .render("templates/auth.rs.tera", &ctx)
+        .with_context(|| "failed to render credentials")?;
+    fs::write(out_dir.as_ref().join("src/auth.rs"), data).await?;
+
+    Ok(())
+}
+
+/// Maps every supported security scheme to a variant. OAuth2 and OpenID
+/// Connect access tokens are sent as bearer tokens; cookies and other HTTP
+/// schemes are skipped.
+pub fn auth_schemes(spec: &OpenAPI) -> Vec<AuthScheme> {
+    let Some(components) = &spec.components else {
+        return Vec::new();
+    };
+
+    let mut schemes: Vec<AuthScheme> = Vec::new();
+    for (name, scheme) in &components.security_schemes {
+        let ReferenceOr::Item(scheme) = scheme else {
+            tracing::warn!(name, "skipping security scheme defined by reference");
+            continue;
+        };
+        let (kind, key) = match scheme {
+            SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("bearer") => {
+                ("bearer", None)
+            }
+            SecurityScheme::HTTP { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => {
+                ("basic", None)
+            }
+            SecurityScheme::APIKey {
+                location: APIKeyLocation::Header,
+                name: key,
+                ..
+            } => ("header", Some(key.clone())),
+            SecurityScheme::APIKey {
+                location: APIKeyLocation::Query,
+                name: key,
+                ..
+            } => ("query", Some(key.clone())),
+            SecurityScheme::OAuth2 { .. } | SecurityScheme::OpenIDConnect { .. } => {
+                ("bearer", None)
+            }
+            _ => {
+                tracing::warn!(name, "skipping unsupported security scheme");
+                continue;
+            }
+        };
+
+        let variant = unique_ident(&variant_ident(name), |n| {
+            schemes.iter().any(|s| s.variant == n)
+        });
+        schemes.push(AuthScheme {
+            name: name.clone(),
+            variant,
+            kind,
+            key,
+            source: CredentialSource::default(),
+        });
+    }
+    schemes
+}
+
+/// The alternative sets of schemes `op` can be authorized with, falling back
+/// to the spec wide `security`. An empty set allows anonymous requests.
+pub(crate) fn operation_security(spec: &OpenAPI, op: &Operation) -> Vec<Vec<String>> {
+    op.security
+        .as_ref()
+        .or(spec.security.as_ref())
+        .map(|requirements| {
+            // Requirements differing only in their scopes are the same here.
+            let mut alternatives: Vec<Vec<String>> = Vec::new();
+            for requirement in requirements {
+                let schemes: Vec<String> = requirement.keys().cloned().collect();
+                if !alternatives.contains(&schemes) {
+                    alternatives.push(schemes);
+                }
+            }
+            alternatives
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn spec(security: Value) -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": { "title": "test", "version": "1" },
+            "paths": {},
+            "security": security,
+            "components": { "securitySchemes": {
+                "basic": { "type": "http", "scheme": "basic" },
+                "bearer": { "type": "http", "scheme": "bearer" },
+                "cookie": { "type": "apiKey", "in": "cookie", "name": "session" },
+                "key": { "type": "apiKey", "in": "header", "name": "X-Api-Key" },
+                "oauth": { "type": "oauth2", "flows": {} },
+                "token": { "type": "apiKey", "in": "query", "name": "token" },
+            } },
+        }))
+        .unwrap()
+    }
+
+    fn operation(security: Option<Value>) -> Operation {
+        let mut op = json!({ "responses": {} });
+        if let Some(security) = security {
+            op["security"] = security;
+        }
+        serde_json::from_value(op).unwrap()
+    }
+
+    fn security(spec_wide: Value, op: Option<Value>) -> Vec<Vec<String>> {
+        operation_security(&spec(spec_wide), &operation(op))
+    }
+
+    #[test]
+    fn maps_supported_schemes() {
+        let schemes: Vec<_> = auth_schemes(&spec(json!([])))
+            .into_iter()
+            .map(|s| (s.name, s.variant, s.kind, s.key))
+            .collect();
+        assert_eq!(
+            schemes,
+            [
+                ("basic".into(), "Basic".into(), "basic", None),
+                ("bearer".into(), "Bearer".into(), "bearer", None),
+                (
+                    "key".into(),
+                    "Key".into(),
+                    "header",
+                    Some("X-Api-Key".into())
+                ),
+                ("oauth".into(), "Oauth".into(), "bearer", None),
+                (
+                    "token".into(),
+                    "Token".into(),
+                    "query",
+                    Some("token".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_security_needs_no_credentials() {
+        assert!(security(json!([]), None).is_empty());
+        assert_eq!(security(json!([{}]), None), [Vec::<String>::new()]);
+    }
+
+    #[test]
+    fn keeps_anonymous_alternatives() {
+        assert_eq!(
+            security(json!([{}, { "bearer": [] }]), None),
+            [vec![], vec!["bearer".to_string()]]
+        );
+    }
+
+    #[test]
+    fn dedups_alternatives_differing_in_scopes() {
+        let spec_wide = json!([{ "oauth": ["read"] }, { "oauth": ["write"] }]);
+        assert_eq!(security(spec_wide, None), [["oauth"]]);
+    }
+
+    #[test]
+    fn operations_override_spec_wide_security() {
+        let spec_wide = json!([{ "bearer": [] }]);
+        assert_eq!(security(spec_wide.clone(), None), [["bearer"]]);
+        assert_eq!(
+            security(spec_wide.clone(), Some(json!([{ "key": [], "token": [] }]))),
+            [["key", "token"]]
+        );
+        assert!(security(spec_wide, Some(json!([]))).is_empty());
+    }
+}
@@ -11,3 +11,426 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use super::auth::operation_security;
+use super::filter::OperationMatcher;
+use super::model::{core_type, rust_type, validatable_types, MapType, Model, ModelUse};
+use super::naming::{field_ident, module_ident, to_pascal_case, to_snake_case, unique_ident};
+use super::pagination::{self, PaginationOverride, PaginationOverrides};
+use crate::Config;
+use anyhow::{Context, Result};
+use openapiv3::{
+    MediaType, OpenAPI, Operation, Parameter, ReferenceOr, RequestBody, Response, StatusCode,
+};
+use serde::Serialize;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tera::{Context as TeraContext, Tera};
+use tokio::fs;
+
+const DEFAULT_MODULE: &str = "default";
+
+/// Refs to components are followed at most this many times before giving up.
+const MAX_REF_HOPS: usize = 16;
+
+#[derive(Debug, Serialize)]
+pub struct OperationParam {
+    pub wire_name: String,
+    pub setter: String,
+    pub location: &'static str,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationBody {
+    pub ty: String,
+    /// Models named by `ty`, imported by the doc example.
+    pub uses: BTreeSet<ModelUse>,
+    pub required: bool,
+    /// Whether the body is checked by the `validation` feature before sending.
+    pub validated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationPagination {
+    /// Variant of the generated `Pagination` enum.
+    pub style: &'static str,
+    /// Response property holding the items of a page.
+    pub items: String,
+    pub item_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiFunction {
+    pub name: String,
+    pub builder_struct: String,
+    pub method: String,
+    pub path: String,
+    pub path_params: Vec<OperationParam>,
+    pub params: Vec<OperationParam>,
+    pub request_body: Option<OperationBody>,
+    /// Alternative sets of security schemes, see [`operation_security`].
+    pub security: Vec<Vec<String>>,
+    /// Retried like an idempotent request whatever its method.
+    pub retry_safe: bool,
+    /// Set for list operations that get `stream` and `collect_all`.
+    pub pagination: Option<OperationPagination>,
+    pub return_type: String,
+    /// Set when no JSON response is documented, so whatever body comes back
+    /// is discarded and `return_type` is `()`.
+    pub empty_response: bool,
+    pub summary: Option<String>,
+    pub description_lines: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ApiModule {
+    pub name: String,
+    pub functions: Vec<ApiFunction>,
+    pub model_uses: BTreeSet<ModelUse>,
+}
+
+/// Renders one `src/apis/<tag>.rs` per operation tag and the `src/apis/mod.rs`
+/// that declares them.
+pub async fn generate_client(
+    tera: &Tera,
+    config: &Config,
+    spec: &OpenAPI,
+    models: &[Model],
+    out_dir: impl AsRef<Path>,
+) -> Result<Vec<ApiModule>> {
+    let apis_dir = out_dir.as_ref().join("src/apis");
+    let retry_safe =
+        OperationMatcher::new(config.retry_safe()).with_context(|| "invalid retry_safe entry")?;
+    let pagination = PaginationOverrides::new(config)?;
+    let mut modules = build_modules(spec, config.map_type(), &retry_safe, &pagination);
+    let validatable = validatable_types(models);
+    for function in modules.iter_mut().flat_map(|m| m.functions.iter_mut()) {
+        if let Some(body) = &mut function.request_body {
+            body.validated = validatable.contains(core_type(&body.ty));
+        }
+    }
+    let crate_name = config.crate_name.replace('-', "_");
+
+    for module in &modules {
+        let mut ctx = TeraContext::new();
+        ctx.insert("crate_name", &crate_name);
+        ctx.insert("module_name", &module.name);
+        ctx.insert("functions", &module.functions);
+        ctx.insert("model_uses", &module.model_uses);
+
+        let data = tera
+            .render("templates/operation.rs.tera", &ctx)
+            .with_context(|| format!("failed to render api module {}", module.name))?;
+        fs::write(apis_dir.join(format!("{}.rs", module.name)), data).await?;
+    }
+
+    let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    let mut ctx = TeraContext::new();
+    ctx.insert("modules", &names);
+    let data = tera.render("templates/mod.rs.tera", &ctx)?;
+    fs::write(apis_dir.join("mod.rs"), data).await?;
+
+    Ok(modules)
+}
+
+/// Groups every operation in `paths` into modules keyed by its first tag.
+/// Operations matched by `retry_safe` or marked `x-retry-safe: true` are
+/// retried whatever their method, and list operations are paginated as
+/// detected or configured in `pagination`.
+pub(crate) fn build_modules(
+    spec: &OpenAPI,
+    map: MapType,
+    retry_safe: &OperationMatcher,
+    pagination: &PaginationOverrides,
+) -> Vec<ApiModule> {
+    let mut modules: BTreeMap<String, ApiModule> = BTreeMap::new();
+
+    for (path, item) in spec.paths.iter() {
+        let ReferenceOr::Item(item) = item else {
+            tracing::warn!(path, "skipping path item defined by reference");
+            continue;
+        };
+
+        for (method, op) in item.iter() {
+            let module_name = api_module_name(op);
+
+            let module = modules
+                .entry(module_name.clone())
+                .or_insert_with(|| ApiModule {
+                    name: module_name,
+                    ..Default::default()
+                });
+
+            let mut function =
+                build_function(spec, path, method, &item.parameters, op, module, map);
+            function.retry_safe = retry_safe.matches(path, op)
+                || op.extensions.get("x-retry-safe") == Some(&serde_json::Value::Bool(true));
+            function.pagination =
+                list_pagination(spec, op, &function, pagination.get(path, op), module, map);
+            module.functions.push(function);
+        }
+    }
+
+    modules.into_values().collect()
+}
+
+/// Names the module of an operation after its first tag.
+pub(crate) fn api_module_name(op: &Operation) -> String {
+    op.tags
+        .first()
+        .map(|t| module_ident(t))
+        .unwrap_or_else(|| DEFAULT_MODULE.to_string())
+}
+
+fn build_function(
+    spec: &OpenAPI,
+    path: &str,
+    method: &str,
+    shared_params: &[ReferenceOr<Parameter>],
+    op: &Operation,
+    module: &mut ApiModule,
+    map: MapType,
+) -> ApiFunction {
+    let op_id = op
+        .operation_id
+        .clone()
+        .unwrap_or_else(|| format!("{method} {path}"));
+    // Operation ids that only differ in case or punctuation, e.g.
+    // `getServer` and `get_server`, still get functions of their own.
+    let (name, _) = field_ident(&op_id);
+    let name = unique_ident(&name, |n| module.functions.iter().any(|f| f.name == n));
+    let builder_struct = unique_ident(&format!("{}Builder", to_pascal_case(&op_id)), |n| {
+        module.functions.iter().any(|f| f.builder_struct == n)
+    });
+
+    let mut path_params = Vec::new();
+    let mut params = Vec::new();
+    for param in merged_parameters(spec, shared_params, &op.parameters) {
+        let (location, data) = match param {
+            Parameter::Path { parameter_data, .. } => ("Path", parameter_data),
+            Parameter::Query { parameter_data, .. } => ("Query", parameter_data),
+            Parameter::Header { parameter_data, .. } => ("Header", parameter_data),
+            Parameter::Cookie { parameter_data, .. } => {
+                tracing::warn!(
+                    op_id,
+                    name = parameter_data.name,
+                    "skipping cookie parameter"
+                );
+                continue;
+            }
+        };
+
+        let setter = unique_ident(&to_snake_case(&data.name), |n| {
+            path_params
+                .iter()
+                .chain(&params)
+                .any(|p: &OperationParam| p.setter == n)
+        });
+        let param = OperationParam {
+            wire_name: data.name.clone(),
+            setter,
+            location,
+            required: data.required,
+        };
+
+        if location == "Path" {
+            path_params.push(param);
+        } else {
+            params.push(param);
+        }
+    }
+
+    let request_body = op
+        .request_body
+        .as_ref()
+        .and_then(|body| request_body(spec, body))
+        .and_then(|body| {
+            let schema = json_media(&body.content)?.schema.as_ref()?;
+            let mut uses = BTreeSet::new();
+            let ty = rust_type(schema, &mut uses, map);
+            module.model_uses.extend(uses.iter().cloned());
+            Some(OperationBody {
+                ty,
+                uses,
+                required: body.required,
+                validated: false,
+            })
+        });
+
+    let return_type = success_response(spec, op)
+        .and_then(|resp| json_media(&resp.content)?.schema.as_ref())
+        .map(|schema| rust_type(schema, &mut module.model_uses, map));
+    let empty_response = return_type.is_none();
+
+    ApiFunction {
+        builder_struct,
+        name,
+        method: method.to_uppercase(),
+        path: path.to_string(),
+        path_params,
+        params,
+        request_body,
+        security: operation_security(spec, op),
+        retry_safe: false,
+        pagination: None,
+        return_type: return_type.unwrap_or_else(|| "()".to_string()),
+        empty_response,
+        summary: op
+            .summary
+            .as_deref()
+            .and_then(|s| s.lines().next())
+            .map(|s| s.trim().to_string()),
+        description_lines: op.description.as_deref().map(doc_lines).unwrap_or_default(),
+    }
+}
+
+/// Pagination of a `GET` operation whose response matches one of the known
+/// styles, or of any operation configured in `pagination`.
+fn list_pagination(
+    spec: &OpenAPI,
+    op: &Operation,
+    function: &ApiFunction,
+    forced: Option<&PaginationOverride>,
+    module: &mut ApiModule,
+    map: MapType,
+) -> Option<OperationPagination> {
+    if forced.is_none() && function.method != "GET" {
+        return None;
+    }
+    let schema =
+        success_response(spec, op).and_then(|resp| json_media(&resp.content)?.schema.as_ref())?;
+    let page_param = function
+        .params
+        .iter()
+        .any(|p| p.location == "Query" && p.wire_name == "page");
+    let paging = pagination::detect(spec.components.as_ref()?, schema, page_param, forced)?;
+
+    Some(OperationPagination {
+        style: paging.variant,
+        items: paging.items,
+        item_type: rust_type(&paging.item_schema, &mut module.model_uses, map),
+    })
+}
+
+/// Operation level parameters override path level ones with the same name
+/// and location.
+fn merged_parameters<'a>(
+    spec: &'a OpenAPI,
+    shared: &'a [ReferenceOr<Parameter>],
+    own: &'a [ReferenceOr<Parameter>],
+) -> Vec<&'a Parameter> {
+    let own: Vec<&Parameter> = own.iter().filter_map(|p| parameter(spec, p)).collect();
+    let mut merged: Vec<&Parameter> = shared
+        .iter()
+        .filter_map(|p| parameter(spec, p))
+        .filter(|shared| !own.iter().any(|o| same_parameter(o, shared)))
+        .collect();
+    merged.extend(own);
+    merged
+}
+
+fn same_parameter(a: &Parameter, b: &Parameter) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+        && a.parameter_data_ref().name == b.parameter_data_ref().name
+}
+
+fn parameter<'a>(spec: &'a OpenAPI, param: &'a ReferenceOr<Parameter>) -> Option<&'a Parameter> {
+    follow(param, "#/components/parameters/", |name| {
+        spec.components.as_ref()?.parameters.get(name)
+    })
+}
+
+fn request_body<'a>(
+    spec: &'a OpenAPI,
+    body: &'a ReferenceOr<RequestBody>,
+) -> Option<&'a RequestBody> {
+    follow(body, "#/components/requestBodies/", |name| {
+        spec.components.as_ref()?.request_bodies.get(name)
+    })
+}
+
+fn response<'a>(spec: &'a OpenAPI, resp: &'a ReferenceOr<Response>) -> Option<&'a Response> {
+    follow(resp, "#/components/responses/", |name| {
+        spec.components.as_ref()?.responses.get(name)
+    })
+}
+
+/// Follows refs starting with `prefix` to the component `lookup` finds for
+/// the rest of them, e.g. from a property to the schema it names.
+pub(super) fn follow<'a, T: Borrow<U>, U>(
+    current: &'a ReferenceOr<T>,
+    prefix: &str,
+    lookup: impl Fn(&str) -> Option<&'a ReferenceOr<U>>,
+) -> Option<&'a U> {
+    let mut reference = match current {
+        ReferenceOr::Item(item) => return Some(item.borrow()),
+        ReferenceOr::Reference { reference } => reference,
+    };
+    for _ in 0..MAX_REF_HOPS {
+        let Some(next) = reference.strip_prefix(prefix).and_then(&lookup) else {
+            tracing::warn!(reference, "could not follow component reference");
+            return None;
+        };
+        match next {
+            ReferenceOr::Item(item) => return Some(item),
+            ReferenceOr::Reference { reference: next } => reference = next,
+        }
+    }
+
+    None
+}
+
+/// The first declared 2xx response, falling back to a `2XX` range.
+fn success_response<'a>(spec: &'a OpenAPI, op: &'a Operation) -> Option<&'a Response> {
+    let responses = &op.responses.responses;
+    responses
+        .iter()
+        .find(|(code, _)| matches!(code, StatusCode::Code(c) if (200..300).contains(c)))
+        .or_else(|| {
+            responses
+                .iter()
+                .find(|(code, _)| matches!(code, StatusCode::Range(2)))
+        })
+        .and_then(|(_, resp)| response(spec, resp))
+}
+
+fn json_media<'a>(
+    content: impl IntoIterator<Item = (&'a String, &'a MediaType)>,
+) -> Option<&'a MediaType> {
+    content
+        .into_iter()
+        .find(|(mime, _)| is_json(mime))
+        .map(|(_, media)| media)
+}
+
+fn is_json(mime: &str) -> bool {
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json") || essence == "*/*"
+}
+
+/// Splits a description into doc comment lines. Untagged code fences are
+/// marked as `text` so rustdoc does not try to compile them as doctests.
+pub(crate) fn doc_lines(text: &str) -> Vec<String> {
+    let mut in_fence = false;
+
+    text.trim()
+        .lines()
+        .map(|line| {
+            let line = line.trim_end();
+            let fence = line.trim_start();
+            if !fence.starts_with("```") {
+                return line.to_string();
+            }
+
+            let opens_untagged = !in_fence && fence == "```";
+            in_fence = !in_fence;
+            if opens_untagged {
+                format!("{line}text")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
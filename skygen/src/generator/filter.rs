@@ -0,0 +1,367 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::naming::module_ident;
+use crate::resolver::pointer::escape_token;
+use crate::resolver::resolve::SCHEMA_REF_PREFIX;
+use crate::Config;
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use openapiv3::{OpenAPI, Operation, ReferenceOr};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// A single `include_only`/`exclude` entry. Entries starting with `/` are path
+/// globs, anything else matches an operation tag or `operationId`.
+#[derive(Debug)]
+enum Filter {
+    Path(Pattern),
+    Name(String),
+}
+
+impl Filter {
+    fn parse(raw: &str) -> Result<Self> {
+        if raw.starts_with('/') {
+            let pattern =
+                Pattern::new(raw).with_context(|| format!("invalid path glob in filter: {raw}"))?;
+            Ok(Self::Path(pattern))
+        } else {
+            Ok(Self::Name(raw.to_string()))
+        }
+    }
+
+    fn matches(&self, path: &str, op: &Operation) -> bool {
+        match self {
+            Self::Path(pattern) => pattern.matches_with(
+                path,
+                MatchOptions {
+                    require_literal_separator: true,
+                    ..MatchOptions::new()
+                },
+            ),
+            Self::Name(name) => {
+                op.operation_id.as_deref() == Some(name.as_str())
+                    || op
+                        .tags
+                        .iter()
+                        .any(|tag| tag == name || module_ident(tag) == *name)
+            }
+        }
+    }
+}
+
+/// Operations selected by a list of entries written like `include_only`.
+#[derive(Debug, Default)]
+pub(crate) struct OperationMatcher(Vec<Filter>);
+
+impl OperationMatcher {
+    pub(crate) fn new(entries: &[String]) -> Result<Self> {
+        Ok(Self(parse_filters(Some(entries))?.unwrap_or_default()))
+    }
+
+    pub(crate) fn matches(&self, path: &str, op: &Operation) -> bool {
+        self.0.iter().any(|f| f.matches(path, op))
+    }
+}
+
+fn parse_filters(raw: Option<&[String]>) -> Result<Option<Vec<Filter>>> {
+    raw.map(|entries| entries.iter().map(|e| Filter::parse(e)).collect())
+        .transpose()
+}
+
+/// Drops the operations rejected by `include_only`/`exclude` and every
+/// component schema, request body, response and parameter that is no longer
+/// reachable from the remaining ones.
+pub fn apply_filters(config: &Config, spec: &mut OpenAPI) -> Result<()> {
+    let include = parse_filters(config.include_only.as_deref())?;
+    let exclude = parse_filters(config.exclude.as_deref())?;
+    if include.is_none() && exclude.is_none() {
+        return Ok(());
+    }
+
+    let keep = |path: &str, op: &Operation| {
+        let included = include
+            .as_ref()
+            .is_none_or(|filters| filters.iter().any(|f| f.matches(path, op)));
+        let excluded = exclude
+            .as_ref()
+            .is_some_and(|filters| filters.iter().any(|f| f.matches(path, op)));
+        included && !excluded
+    };
+
+    let mut kept_ops = 0;
+    for (path, item) in spec.paths.paths.iter_mut() {
+        let ReferenceOr::Item(item) = item else {
+            continue;
+        };
+
+        for slot in [
+            &mut item.get,
+            &mut item.put,
+            &mut item.post,
+            &mut item.delete,
+            &mut item.options,
+            &mut item.head,
+            &mut item.patch,
+            &mut item.trace,
+        ] {
+            if slot.as_ref().is_some_and(|op| keep(path, op)) {
+                kept_ops += 1;
+            } else {
+                *slot = None;
+            }
+        }
+    }
+
+    spec.paths.paths.retain(|_, item| match item {
+        ReferenceOr::Item(item) => item.iter().next().is_some(),
+        ReferenceOr::Reference { .. } => true,
+    });
+
+    let pruned = prune_components(spec)?;
+    tracing::info!(
+        operations = kept_ops,
+        pruned_components = pruned,
+        "applied filters"
+    );
+
+    Ok(())
+}
+
+/// Removes the component schemas, request bodies, responses and parameters
+/// that cannot be reached from `paths`, following refs transitively through
+/// every kind of component. Returns the number of components removed.
+fn prune_components(spec: &mut OpenAPI) -> Result<usize> {
+    let root = serde_json::to_value(&*spec).with_context(|| "failed to serialize spec")?;
+
+    let mut pending = Vec::new();
+    collect_refs(&root["paths"], &mut pending);
+
+    let mut seen = HashSet::new();
+    while let Some(reference) = pending.pop() {
+        if !seen.insert(reference.clone()) {
+            continue;
+        }
+
+        if let Some(target) = reference.strip_prefix('#').and_then(|p| root.pointer(p)) {
+            collect_refs(target, &mut pending);
+        }
+    }
+
+    let Some(components) = spec.components.as_mut() else {
+        return Ok(0);
+    };
+
+    let reachable = |section: &str, name: &str| {
+        seen.contains(&format!("#/components/{section}/{}", escape_token(name)))
+    };
+    let before = components.schemas.len()
+        + components.request_bodies.len()
+        + components.responses.len()
+        + components.parameters.len();
+    components
+        .schemas
+        .retain(|name, _| reachable("schemas", name));
+    components
+        .request_bodies
+        .retain(|name, _| reachable("requestBodies", name));
+    components
+        .responses
+        .retain(|name, _| reachable("responses", name));
+    components
+        .parameters
+        .retain(|name, _| reachable("parameters", name));
+    let after = components.schemas.len()
+        + components.request_bodies.len()
+        + components.responses.len()
+        + components.parameters.len();
+
+    Ok(before - after)
+}
+
+/// Collects every `$ref` in `value`, along with the schemas named by
+/// `discriminator.mapping` values, either refs or bare schema names.
+pub(crate) fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.push(reference.clone());
+            }
+            if let Some(Value::Object(mapping)) =
+                map.get("discriminator").and_then(|d| d.get("mapping"))
+            {
+                for target in mapping.values().filter_map(Value::as_str) {
+                    if target.contains('/') {
+                        refs.push(target.to_string());
+                    } else {
+                        refs.push(format!("{SCHEMA_REF_PREFIX}{}", escape_token(target)));
+                    }
+                }
+            }
+            for v in map.values() {
+                collect_refs(v, refs);
+            }
+        }
+        Value::Array(arr) => {
+            for v in arr {
+                collect_refs(v, refs);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn operation(operation_id: &str, tags: &[&str]) -> Operation {
+        Operation {
+            operation_id: Some(operation_id.to_string()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Operation::default()
+        }
+    }
+
+    fn config(filters: &str) -> Config {
+        let raw = format!(
+            r#"
+            crate_name = "test"
+            version = "0.1.0"
+            description = "test"
+            lib_status = "experimental"
+            keywords = []
+            api_url = "https://api.example.com"
+            authors = []
+            {filters}
+            "#
+        );
+        toml::from_str(&raw).unwrap()
+    }
+
+    fn spec() -> OpenAPI {
+        let schema_op = |id: &str, tag: &str, schema: &str| {
+            json!({
+                "operationId": id,
+                "tags": [tag],
+                "parameters": [{ "$ref": format!("#/components/parameters/{id}_page") }],
+                "responses": { "200": {
+                    "description": "ok",
+                    "content": { "application/json": {
+                        "schema": { "$ref": format!("#/components/schemas/{schema}") },
+                    } },
+                } },
+            })
+        };
+        let page = json!({ "name": "page", "in": "query", "schema": { "type": "integer" } });
+        serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": { "title": "test", "version": "1" },
+            "paths": {
+                "/v2/droplets": { "get": schema_op("droplets_list", "Droplets", "Droplets") },
+                "/v2/domains": { "get": schema_op("domains_list", "Domains", "Domain") },
+            },
+            "components": {
+                "schemas": {
+                    "Droplets": {
+                        "type": "array",
+                        "items": { "$ref": "#/components/schemas/Pet" },
+                    },
+                    "Pet": {
+                        "oneOf": [{ "$ref": "#/components/schemas/Cat" }],
+                        "discriminator": { "propertyName": "kind", "mapping": { "dog": "Dog" } },
+                    },
+                    "Cat": { "type": "object" },
+                    "Dog": { "type": "object" },
+                    "Domain": { "type": "object" },
+                    "Unused": { "type": "object" },
+                },
+                "parameters": { "droplets_list_page": page, "domains_list_page": page },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn names<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<&'a str> {
+        keys.map(String::as_str).collect()
+    }
+
+    #[test]
+    fn path_globs_stop_at_separators() {
+        let op = operation("droplets_get", &[]);
+        let single = Filter::parse("/v2/droplets/*").unwrap();
+        assert!(single.matches("/v2/droplets/{id}", &op));
+        assert!(!single.matches("/v2/droplets/{id}/actions", &op));
+        assert!(!single.matches("/v2/droplets", &op));
+
+        let nested = Filter::parse("/v2/droplets/**").unwrap();
+        assert!(nested.matches("/v2/droplets/{id}/actions", &op));
+    }
+
+    #[test]
+    fn names_match_tags_modules_and_operation_ids() {
+        let op = operation("droplets_list", &["Domain Records"]);
+        for name in ["Domain Records", "domain_records", "droplets_list"] {
+            assert!(Filter::parse(name).unwrap().matches("/", &op), "{name}");
+        }
+        assert!(!Filter::parse("Domain").unwrap().matches("/", &op));
+    }
+
+    #[test]
+    fn matcher_needs_any_entry() {
+        let op = operation("droplets_list", &["Droplets"]);
+        let matcher = OperationMatcher::new(&["Domains".into(), "/v2/**".into()]).unwrap();
+        assert!(matcher.matches("/v2/droplets", &op));
+        assert!(!OperationMatcher::default().matches("/v2/droplets", &op));
+        assert!(OperationMatcher::new(&["/v2/[".into()]).is_err());
+    }
+
+    #[test]
+    fn leaves_the_spec_alone_without_filters() {
+        let mut spec = spec();
+        apply_filters(&config(""), &mut spec).unwrap();
+        assert_eq!(spec.paths.paths.len(), 2);
+        assert_eq!(spec.components.unwrap().schemas.len(), 6);
+    }
+
+    #[test]
+    fn prunes_components_of_dropped_operations() {
+        let mut spec = spec();
+        apply_filters(&config(r#"include_only = ["Droplets"]"#), &mut spec).unwrap();
+
+        assert_eq!(names(spec.paths.paths.keys()), ["/v2/droplets"]);
+        let components = spec.components.unwrap();
+        assert_eq!(
+            names(components.schemas.keys()),
+            ["Cat", "Dog", "Droplets", "Pet"]
+        );
+        assert_eq!(names(components.parameters.keys()), ["droplets_list_page"]);
+    }
+
+    #[test]
+    fn exclude_wins_over_include_only() {
+        let mut spec = spec();
+        let filters = r#"
+            include_only = ["/v2/*"]
+            exclude = ["droplets_list"]
+        "#;
+        apply_filters(&config(filters), &mut spec).unwrap();
+
+        assert_eq!(names(spec.paths.paths.keys()), ["/v2/domains"]);
+        let components = spec.components.unwrap();
+        assert_eq!(names(components.schemas.keys()), ["Domain"]);
+        assert_eq!(names(components.parameters.keys()), ["domains_list_page"]);
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::model::is_annotation;
+use super::naming::type_ident;
+use crate::resolver::resolve::SCHEMA_REF_PREFIX;
+use openapiv3::{
+    AdditionalProperties, Content, OpenAPI, ReferenceOr, Schema, SchemaData, SchemaKind,
+    StatusCode, Type,
+};
+use serde_json::Value;
+use std::borrow::BorrowMut;
+use std::collections::{HashMap, HashSet};
+
+/// Moves inline schemas that need a named type of their own into
+/// `components.schemas`, leaving a ref behind.
+///
+/// Request and response schemas are named after their operationId, e.g.
+/// `droplet_actions_post_request`; non-2xx responses add their status code.
+/// Shared `requestBodies` and `responses` components are left alone: the
+/// resolver already copied them into the operations using them, so hoisting
+/// them as well would only add models nothing refers to. Nested objects are
+/// named after the type and property path containing them, e.g.
+/// `create_server_request_public_net`, and structurally identical ones share
+/// the first name given to them.
+pub fn hoist_inline_schemas(spec: &mut OpenAPI) {
+    let mut hoister = Hoister {
+        taken: spec
+            .components
+            .iter()
+            .flat_map(|c| c.schemas.keys())
+            .map(|name| type_ident(name))
+            .collect(),
+        ..Hoister::default()
+    };
+
+    if let Some(components) = &mut spec.components {
+        for (name, schema) in components.schemas.iter_mut() {
+            let ReferenceOr::Item(schema) = schema else {
+                continue;
+            };
+            hoister.name_nested(name, schema);
+            if is_inline_object(schema) {
+                hoister
+                    .shapes
+                    .entry(shape_key(schema))
+                    .or_insert_with(|| name.clone());
+            }
+        }
+    }
+
+    for (path, item) in spec.paths.paths.iter_mut() {
+        let ReferenceOr::Item(item) = item else {
+            continue;
+        };
+
+        for (method, slot) in [
+            ("get", &mut item.get),
+            ("put", &mut item.put),
+            ("post", &mut item.post),
+            ("delete", &mut item.delete),
+            ("options", &mut item.options),
+            ("head", &mut item.head),
+            ("patch", &mut item.patch),
+            ("trace", &mut item.trace),
+        ] {
+            let Some(op) = slot else {
+                continue;
+            };
+            let op_id = op
+                .operation_id
+                .clone()
+                .unwrap_or_else(|| format!("{method} {path}"));
+
+            if let Some(ReferenceOr::Item(body)) = &mut op.request_body {
+                let name = format!("{op_id}_request");
+                hoister.content(&name, &mut body.content);
+            }
+            for (status, resp) in op.responses.responses.iter_mut() {
+                if let ReferenceOr::Item(resp) = resp {
+                    let name = match status {
+                        StatusCode::Code(200..=299) | StatusCode::Range(2) => {
+                            format!("{op_id}_response")
+                        }
+                        _ => format!("{op_id}_response_{}", status.to_string().to_lowercase()),
+                    };
+                    hoister.content(&name, &mut resp.content);
+                }
+            }
+        }
+    }
+
+    if hoister.hoisted.is_empty() {
+        return;
+    }
+    tracing::debug!(count = hoister.hoisted.len(), "hoisted inline schemas");
+    let components = spec.components.get_or_insert_with(Default::default);
+    components.schemas.extend(hoister.hoisted);
+}
+
+#[derive(Debug, Default)]
+struct Hoister {
+    /// Type identifiers of every component schema, hoisted ones included.
+    taken: HashSet<String>,
+    /// Component name of the first object seen with a given [`shape_key`].
+    shapes: HashMap<String, String>,
+    hoisted: Vec<(String, ReferenceOr<Schema>)>,
+}
+
+impl Hoister {
+    /// Hoists the schemas of a request or response body named `base`.
+    /// Operations keep types of their own even when their shapes match.
+    fn content(&mut self, base: &str, content: &mut Content) {
+        for media in content.values_mut() {
+            let Some(ReferenceOr::Item(schema)) = &mut media.schema else {
+                continue;
+            };
+            self.name_nested(base, schema);
+            if !needs_name(schema) && !is_inline_object(schema) {
+                continue;
+            }
+
+            let name = self.claim(base);
+            self.shapes
+                .entry(shape_key(schema))
+                .or_insert_with(|| name.clone());
+            let reference = ReferenceOr::Reference {
+                reference: format!("{SCHEMA_REF_PREFIX}{name}"),
+            };
+            if let Some(schema) = media.schema.replace(reference) {
+                self.hoisted.push((name, schema));
+            }
+        }
+    }
+
+    /// Hoists the inline objects nested in `schema`, innermost first so that
+    /// the shapes compared for duplicates only refer to named children.
+    fn name_nested(&mut self, ctx: &str, schema: &mut Schema) {
+        match &mut schema.schema_kind {
+            SchemaKind::Type(Type::Object(obj)) => {
+                for (prop, slot) in obj.properties.iter_mut() {
+                    self.slot(&format!("{ctx}_{prop}"), slot);
+                }
+                if let Some(AdditionalProperties::Schema(slot)) = &mut obj.additional_properties {
+                    self.slot(&format!("{ctx}_value"), slot.as_mut());
+                }
+            }
+            SchemaKind::Type(Type::Array(arr)) => {
+                if let Some(slot) = &mut arr.items {
+                    self.slot(&format!("{ctx}_item"), slot);
+                }
+            }
+            // Members of a composition are merged into the composed struct.
+            SchemaKind::AllOf { all_of: members } => {
+                for member in members.iter_mut() {
+                    if let ReferenceOr::Item(member) = member {
+                        self.name_nested(ctx, member);
+                    }
+                }
+            }
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members } => {
+                for (n, slot) in members.iter_mut().enumerate() {
+                    self.slot(&format!("{ctx}_variant_{}", n + 1), slot);
+                }
+            }
+            SchemaKind::Any(any) => {
+                for (prop, slot) in any.properties.iter_mut() {
+                    self.slot(&format!("{ctx}_{prop}"), slot);
+                }
+                if let Some(AdditionalProperties::Schema(slot)) = &mut any.additional_properties {
+                    self.slot(&format!("{ctx}_value"), slot.as_mut());
+                }
+                if let Some(slot) = &mut any.items {
+                    self.slot(&format!("{ctx}_item"), slot);
+                }
+                for member in any.all_of.iter_mut() {
+                    if let ReferenceOr::Item(member) = member {
+                        self.name_nested(ctx, member);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces an inline object in `slot` with a ref to its hoisted copy.
+    /// Nullable, `readOnly` and `writeOnly` objects keep those flags on a
+    /// single member `allOf`, since siblings of a `$ref` are ignored.
+    fn slot<S>(&mut self, ctx: &str, slot: &mut ReferenceOr<S>)
+    where
+        S: BorrowMut<Schema> + From<Schema>,
+    {
+        let ReferenceOr::Item(schema) = slot else {
+            return;
+        };
+        let schema: &mut Schema = schema.borrow_mut();
+        self.name_nested(ctx, schema);
+        if !is_inline_object(schema) {
+            return;
+        }
+
+        let mut schema = schema.clone();
+        let nullable = std::mem::take(&mut schema.schema_data.nullable);
+        let read_only = std::mem::take(&mut schema.schema_data.read_only);
+        let write_only = std::mem::take(&mut schema.schema_data.write_only);
+        let key = shape_key(&schema);
+        let name = match self.shapes.get(&key) {
+            Some(name) => name.clone(),
+            None => {
+                let name = self.claim(ctx);
+                self.shapes.insert(key, name.clone());
+                self.hoisted.push((name.clone(), ReferenceOr::Item(schema)));
+                name
+            }
+        };
+
+        let reference = format!("{SCHEMA_REF_PREFIX}{name}");
+        *slot = if nullable || read_only || write_only {
+            ReferenceOr::Item(S::from(Schema {
+                schema_data: SchemaData {
+                    nullable,
+                    read_only,
+                    write_only,
+                    ..SchemaData::default()
+                },
+                schema_kind: SchemaKind::AllOf {
+                    all_of: vec![ReferenceOr::Reference { reference }],
+                },
+            }))
+        } else {
+            ReferenceOr::Reference { reference }
+        };
+    }
+
+    /// Returns a component name derived from `base` that is not taken yet.
+    fn claim(&mut self, base: &str) -> String {
+        let base: String = base
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.taken.contains(&type_ident(&name)) {
+            name = format!("{base}_{n}");
+            n += 1;
+        }
+        self.taken.insert(type_ident(&name));
+        name
+    }
+}
+
+/// Unions can only be represented by a named enum and compositions by a
+/// named struct merging their members.
+fn needs_name(schema: &Schema) -> bool {
+    matches!(&schema.schema_kind,
+        SchemaKind::OneOf { one_of: members }
+            | SchemaKind::AnyOf { any_of: members }
+            | SchemaKind::AllOf { all_of: members }
+        if members.len() > 1)
+}
+
+/// Objects with named properties, and compositions of more than one typed
+/// member, are generated as structs.
+fn is_inline_object(schema: &Schema) -> bool {
+    let composed = |members: &[ReferenceOr<Schema>]| {
+        members
+            .iter()
+            .filter(|m| match m {
+                ReferenceOr::Item(member) => !is_annotation(member),
+                ReferenceOr::Reference { .. } => true,
+            })
+            .count()
+            > 1
+    };
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => !obj.properties.is_empty(),
+        SchemaKind::AllOf { all_of } => composed(all_of),
+        SchemaKind::Any(any) => {
+            matches!(any.typ.as_deref(), None | Some("object"))
+                && (!any.properties.is_empty() || composed(&any.all_of))
+        }
+        _ => false,
+    }
+}
+
+/// Identifies the shape of an object regardless of its documentation.
+fn shape_key(schema: &Schema) -> String {
+    let mut value = serde_json::to_value(schema).unwrap_or_default();
+    strip_annotations(&mut value);
+    if let Value::Object(map) = &mut value {
+        for key in ["nullable", "readOnly", "writeOnly"] {
+            map.remove(key);
+        }
+    }
+    value.to_string()
+}
+
+fn strip_annotations(schema: &mut Value) {
+    let Value::Object(map) = schema else {
+        return;
+    };
+    for key in ["description", "title", "example", "externalDocs"] {
+        map.remove(key);
+    }
+    if let Some(Value::Object(props)) = map.get_mut("properties") {
+        props.values_mut().for_each(strip_annotations);
+    }
+    for key in ["items", "additionalProperties", "not"] {
+        if let Some(child) = map.get_mut(key) {
+            strip_annotations(child);
+        }
+    }
+    for key in ["allOf", "oneOf", "anyOf"] {
+        if let Some(Value::Array(members)) = map.get_mut(key) {
+            members.iter_mut().for_each(strip_annotations);
+        }
+    }
+}
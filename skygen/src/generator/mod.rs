@@ -12,6 +12,107 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod auth;
 pub mod client;
+pub mod filter;
+pub mod inline;
 pub mod model;
+pub mod naming;
+pub mod pagination;
 pub mod project;
+pub mod read_write;
+
+use crate::resolver::resolve::Resolver;
+use crate::Config;
+use anyhow::{Context, Result};
+use openapiv3::OpenAPI;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Loads the spec at `path` and converts it into an OpenAPI document.
+///
+/// Refs into other files are inlined first, so a spec split across a tree of
+/// YAML or JSON documents is accepted as well as a bundled one. Resolution
+/// fails once values nest deeper than `max_depth`, see [`Config::max_depth`].
+pub fn load_spec(path: impl AsRef<Path>, max_depth: usize) -> Result<OpenAPI> {
+    let resolved = Resolver::from_path(path)?
+        .preserve("#/components/")
+        .max_depth(max_depth)
+        .resolve()
+        .with_context(|| "failed to resolve spec references")?;
+
+    serde_json::from_value(resolved).with_context(|| "failed to convert into OpenAPIv3 spec")
+}
+
+/// Generates a complete SDK crate for `spec` into `out_dir`.
+///
+/// The spec is run through the resolver and the `include_only`/`exclude`
+/// filters before the crate skeleton, the models, the operation builders and
+/// the credentials are rendered. The generated crate is not
+/// formatted; see [`project::format_crate`].
+pub async fn generate(config: &Config, spec: &OpenAPI, out_dir: impl AsRef<Path>) -> Result<()> {
+    let out_dir = out_dir.as_ref();
+
+    let root = serde_json::to_value(spec).with_context(|| "failed to serialize spec")?;
+    // Named schemas become models of their own, so refs to them stay in place
+    // for the generators to map onto type names.
+    let resolved = Resolver::new(root)
+        .preserve("#/components/schemas/")
+        .max_depth(config.max_depth())
+        .resolve()
+        .with_context(|| "failed to resolve spec references")?;
+    let mut spec: OpenAPI = serde_json::from_value(resolved)
+        .with_context(|| "resolved document is not a valid OpenAPIv3 spec")?;
+    filter::apply_filters(config, &mut spec)?;
+    naming::disambiguate_schemas(&mut spec)?;
+    inline::hoist_inline_schemas(&mut spec);
+    if config.read_write() == read_write::ReadWriteMode::Split {
+        read_write::split_schemas(&mut spec)?;
+    }
+
+    project::bootstrap_lib(config, &shared_module_names(&spec), out_dir)
+        .await
+        .with_context(|| "failed to bootstrap library")?;
+
+    let tera = project::load_templates()?;
+    let models = model::generate_models(&tera, config, &spec, out_dir)
+        .await
+        .with_context(|| "failed to generate models")?;
+    client::generate_client(&tera, config, &spec, &models, out_dir)
+        .await
+        .with_context(|| "failed to generate api modules")?;
+    auth::generate_auth(&tera, config, &spec, out_dir)
+        .await
+        .with_context(|| "failed to generate credentials")?;
+
+    Ok(())
+}
+
+/// Names used by both an api and a model module, which the crate prelude
+/// has to re-export explicitly to keep its glob imports unambiguous.
+fn shared_module_names(spec: &OpenAPI) -> Vec<String> {
+    let models: BTreeSet<String> = spec
+        .components
+        .iter()
+        .flat_map(|c| c.schemas.keys())
+        .map(|name| naming::module_ident(name))
+        .collect();
+    let apis: BTreeSet<String> = spec
+        .paths
+        .iter()
+        .filter_map(|(_, item)| item.as_item())
+        .flat_map(|item| item.iter())
+        .map(|(_, op)| client::api_module_name(op))
+        .collect();
+
+    apis.intersection(&models).cloned().collect()
+}
+
+/// Blocking variant of [`generate`] for callers without an async runtime,
+/// such as build scripts.
+pub fn generate_blocking(config: &Config, spec: &OpenAPI, out_dir: impl AsRef<Path>) -> Result<()> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(generate(config, spec, out_dir))
+}
@@ -11,3 +11,1726 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use super::client::doc_lines;
+use super::naming::{
+    field_ident, module_ident, to_pascal_case, type_ident, unique_field, unique_ident,
+    variant_ident,
+};
+use crate::resolver::resolve::SCHEMA_REF_PREFIX;
+use crate::Config;
+use anyhow::{Context, Result};
+use openapiv3::{
+    AdditionalProperties, Components, IntegerFormat, NumberFormat, OpenAPI, ReferenceOr, Schema,
+    SchemaKind, StringFormat, Type, VariantOrUnknownOrEmpty,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use tera::{Context as TeraContext, Tera};
+use tokio::fs;
+
+/// A `use crate::models::<module>::<ty>` line required by a rendered file.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ModelUse {
+    pub module: String,
+    pub ty: String,
+}
+
+/// Container generated for `additionalProperties` maps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapType {
+    #[default]
+    HashMap,
+    BTreeMap,
+}
+
+impl MapType {
+    fn path(self) -> &'static str {
+        match self {
+            Self::HashMap => "std::collections::HashMap",
+            Self::BTreeMap => "std::collections::BTreeMap",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelField {
+    pub name: String,
+    pub rename: Option<String>,
+    pub ty: String,
+    pub required: bool,
+    pub flatten: bool,
+    /// Whether `null` is a valid value. Combined with `required` this tells
+    /// an absent field apart from one that is explicitly null.
+    pub nullable: bool,
+    /// Set by the server only, so never serialized.
+    pub read_only: bool,
+    /// Accepted in requests only, so never deserialized. Always optional.
+    pub write_only: bool,
+    /// Checks of the `validation` feature run against the value.
+    pub constraints: Vec<Constraint>,
+    /// Whether the value is a generated type validated on its own.
+    pub nested: bool,
+}
+
+impl ModelField {
+    /// The property name as it appears on the wire.
+    fn wire_name(&self) -> &str {
+        self.rename
+            .as_deref()
+            .unwrap_or_else(|| self.name.trim_start_matches("r#"))
+    }
+}
+
+/// A variant of a generated enum. `literal` is the wire value as a quoted
+/// Rust string literal and `ty` the payload of union variants.
+#[derive(Debug, Serialize)]
+pub struct EnumVariant {
+    pub name: String,
+    pub literal: Option<String>,
+    pub ty: Option<String>,
+    pub description_lines: Vec<String>,
+    /// Whether the payload is a generated type validated on its own.
+    pub nested: bool,
+}
+
+/// A call to `crate::validation::<check>(field, value, <args>)`, `args`
+/// being the Rust literals following the value.
+#[derive(Debug, Clone, Serialize)]
+pub struct Constraint {
+    pub check: &'static str,
+    pub args: String,
+}
+
+/// A generated type. Models with `variants` are rendered as enums: string
+/// enums keep values unknown at generation time in their `catch_all`
+/// variant, unions are internally tagged by `tag`, untagged or externally
+/// tagged by the variant names.
+#[derive(Debug, Default, Serialize)]
+pub struct Model {
+    pub name: String,
+    pub module: String,
+    pub alias: Option<String>,
+    pub fields: Vec<ModelField>,
+    pub variants: Vec<EnumVariant>,
+    pub catch_all: Option<String>,
+    pub tag: Option<String>,
+    pub untagged: bool,
+    /// Externally tagged by mutually exclusive properties, deserialized from
+    /// the first of them that is set and not null.
+    pub exclusive: bool,
+    /// Types synthesized from inline schemas, rendered into the same module.
+    pub inline: Vec<Model>,
+    pub model_uses: Vec<ModelUse>,
+}
+
+/// Renders one `src/models/<module>.rs` per entry in `components.schemas` and
+/// the `src/models/mod.rs` that declares them.
+pub async fn generate_models(
+    tera: &Tera,
+    config: &Config,
+    spec: &OpenAPI,
+    out_dir: impl AsRef<Path>,
+) -> Result<Vec<Model>> {
+    let models_dir = out_dir.as_ref().join("src/models");
+    let models = build_models(spec, config.map_type());
+
+    let mut modules = BTreeSet::new();
+    for model in &models {
+        let mut ctx = TeraContext::new();
+        ctx.insert("model", model);
+        ctx.insert("model_uses", &model.model_uses);
+
+        let data = tera
+            .render("templates/model.rs.tera", &ctx)
+            .with_context(|| format!("failed to render model {}", model.name))?;
+        fs::write(models_dir.join(format!("{}.rs", model.module)), data).await?;
+
+        modules.insert(model.module.clone());
+    }
+
+    let mut ctx = TeraContext::new();
+    ctx.insert("modules", &modules);
+    let data = tera.render("templates/mod.rs.tera", &ctx)?;
+    fs::write(models_dir.join("mod.rs"), data).await?;
+
+    Ok(models)
+}
+
+/// Builds one model per entry in `components.schemas`.
+pub fn build_models(spec: &OpenAPI, map: MapType) -> Vec<Model> {
+    let Some(components) = &spec.components else {
+        return Vec::new();
+    };
+
+    let taken: HashSet<String> = components.schemas.keys().map(|n| type_ident(n)).collect();
+    let mut models: Vec<Model> = components
+        .schemas
+        .iter()
+        .map(|(name, schema)| build_model(components, name, schema, &taken, map))
+        .collect();
+    share_inline_enums(&mut models);
+    release_tag_fields(&mut models);
+    box_recursive_fields(&mut models);
+    mark_nested(&mut models);
+
+    models
+}
+
+/// Generated structs and unions, which implement `Validate`, and the
+/// aliases of them.
+pub fn validatable_types(models: &[Model]) -> HashSet<String> {
+    let all: Vec<&Model> = models
+        .iter()
+        .flat_map(|m| std::iter::once(m).chain(&m.inline))
+        .collect();
+    let mut types: HashSet<String> = all
+        .iter()
+        .filter(|m| m.alias.is_none() && m.catch_all.is_none())
+        .map(|m| m.name.clone())
+        .collect();
+
+    loop {
+        let aliases: Vec<String> = all
+            .iter()
+            .filter(|m| !types.contains(&m.name))
+            .filter(|m| {
+                m.alias
+                    .as_deref()
+                    .is_some_and(|alias| types.contains(core_type(alias)))
+            })
+            .map(|m| m.name.clone())
+            .collect();
+        if aliases.is_empty() {
+            return types;
+        }
+        types.extend(aliases);
+    }
+}
+
+/// The type held by a field once containers are peeled off, e.g. `Droplet`
+/// for `Option<Vec<Box<Droplet>>>`.
+pub(crate) fn core_type(mut ty: &str) -> &str {
+    const CONTAINERS: [&str; 5] = [
+        "Vec<",
+        "Box<",
+        "Option<",
+        "std::collections::HashMap<String, ",
+        "std::collections::BTreeMap<String, ",
+    ];
+    while let Some(inner) = CONTAINERS
+        .iter()
+        .find_map(|c| ty.strip_prefix(c)?.strip_suffix('>'))
+    {
+        ty = inner;
+    }
+    ty
+}
+
+/// Flags the fields and variants holding a validatable type, whose checks
+/// are delegated to that type.
+fn mark_nested(models: &mut [Model]) {
+    let types = validatable_types(models);
+    let mark = |model: &mut Model| {
+        for field in model.fields.iter_mut() {
+            field.nested = types.contains(core_type(&field.ty));
+        }
+        for variant in model.variants.iter_mut() {
+            variant.nested = variant
+                .ty
+                .as_deref()
+                .is_some_and(|ty| types.contains(core_type(ty)));
+        }
+    };
+    for model in models.iter_mut() {
+        mark(model);
+        model.inline.iter_mut().for_each(mark);
+    }
+}
+
+/// Boxes every field that stores a model inline when that model can reach
+/// the field's struct again without passing through a `Vec` or other heap
+/// indirection, which would otherwise give the struct an infinite size.
+fn box_recursive_fields(models: &mut [Model]) {
+    // Edges from a model to the models it stores inline, aliases included.
+    let edges: HashMap<&str, Vec<&str>> = models
+        .iter()
+        .flat_map(|m| std::iter::once(m).chain(&m.inline))
+        .map(|m| {
+            let inline = match &m.alias {
+                Some(alias) => vec![alias.as_str()],
+                None => m
+                    .fields
+                    .iter()
+                    .map(|f| f.ty.as_str())
+                    .chain(m.variants.iter().filter_map(|v| v.ty.as_deref()))
+                    .collect(),
+            };
+            (m.name.as_str(), inline)
+        })
+        .collect();
+
+    let reaches = |from: &str, to: &str| {
+        let mut stack = vec![from];
+        let mut seen = HashSet::new();
+        while let Some(current) = stack.pop() {
+            if current == to {
+                return true;
+            }
+            if seen.insert(current) {
+                stack.extend(edges.get(current).into_iter().flatten());
+            }
+        }
+        false
+    };
+
+    let boxed: Vec<(usize, usize)> = models
+        .iter()
+        .enumerate()
+        .flat_map(|(mi, m)| {
+            m.fields
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| edges.contains_key(f.ty.as_str()) && reaches(&f.ty, &m.name))
+                .map(move |(fi, _)| (mi, fi))
+        })
+        .collect();
+
+    for (mi, fi) in boxed {
+        let field = &mut models[mi].fields[fi];
+        field.ty = format!("Box<{}>", field.ty);
+    }
+}
+
+/// Properties that several structs inherit from one `allOf` member, e.g. the
+/// `type` of every `DropletAction*`, would each get an identical inline enum.
+/// Enums generated for the same property with the same values are replaced by
+/// the one with the shortest name.
+fn share_inline_enums(models: &mut [Model]) {
+    type EnumKey = (String, Vec<Option<String>>);
+
+    let element = |ty: &str| {
+        ty.strip_prefix("Vec<")
+            .and_then(|t| t.strip_suffix('>'))
+            .unwrap_or(ty)
+            .to_string()
+    };
+    let inline_enums = |model: &Model| {
+        model
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let ty = element(&field.ty);
+                let model = model
+                    .inline
+                    .iter()
+                    .find(|m| m.name == ty && m.catch_all.is_some())?;
+                let literals = model.variants.iter().map(|v| v.literal.clone()).collect();
+                Some(((field.wire_name().to_string(), literals), ty))
+            })
+            .collect::<Vec<(EnumKey, String)>>()
+    };
+
+    let mut shared: HashMap<EnumKey, (String, String)> = HashMap::new();
+    for model in models.iter() {
+        for (key, name) in inline_enums(model) {
+            let candidate = (model.module.clone(), name);
+            shared
+                .entry(key)
+                .and_modify(|current| {
+                    if candidate.1.len() < current.1.len() {
+                        *current = candidate.clone();
+                    }
+                })
+                .or_insert(candidate);
+        }
+    }
+
+    for model in models.iter_mut() {
+        for (key, name) in inline_enums(model) {
+            let (module, canonical) = &shared[&key];
+            if *canonical == name {
+                continue;
+            }
+
+            model.inline.retain(|m| m.name != name);
+            for field in model.fields.iter_mut() {
+                if field.ty == name {
+                    field.ty = canonical.clone();
+                } else if element(&field.ty) == name {
+                    field.ty = format!("Vec<{canonical}>");
+                }
+            }
+            let import = ModelUse {
+                module: module.clone(),
+                ty: canonical.clone(),
+            };
+            if *module != model.module && !model.model_uses.contains(&import) {
+                model.model_uses.push(import);
+                model.model_uses.sort();
+            }
+        }
+    }
+}
+
+/// Internally tagged enums consume their tag before deserializing a variant,
+/// so the tag property of every member struct has to be optional, which
+/// also leaves it out when serializing.
+fn release_tag_fields(models: &mut [Model]) {
+    let tagged: HashSet<(String, String)> = models
+        .iter()
+        .flat_map(|m| std::iter::once(m).chain(&m.inline))
+        .filter_map(|m| Some((m.tag.as_deref()?, &m.variants)))
+        .flat_map(|(tag, variants)| {
+            let tag = serde_json::from_str::<String>(tag).unwrap_or_default();
+            variants
+                .iter()
+                .filter_map(move |v| Some((v.ty.clone()?, tag.clone())))
+        })
+        .collect();
+
+    for model in models.iter_mut() {
+        for field in model.fields.iter_mut() {
+            if tagged.contains(&(model.name.clone(), field.wire_name().to_string())) {
+                field.required = false;
+            }
+        }
+    }
+}
+
+fn build_model(
+    components: &Components,
+    name: &str,
+    schema: &ReferenceOr<Schema>,
+    taken: &HashSet<String>,
+    map: MapType,
+) -> Model {
+    let mut uses = BTreeSet::new();
+    let type_name = type_ident(name);
+    let module = module_ident(name);
+
+    let item = match schema {
+        ReferenceOr::Item(item) => Some(item),
+        ReferenceOr::Reference { .. } => None,
+    };
+
+    if let Some(model) = item.and_then(|item| enum_model(&type_name, &module, item)) {
+        return model;
+    }
+    if let Some(mut model) =
+        item.and_then(|item| union_model(&type_name, &module, item, &mut uses, map))
+    {
+        uses.retain(|u| u.ty != type_name);
+        model.model_uses = uses.into_iter().collect();
+        return model;
+    }
+
+    let mut inline = Vec::new();
+    let shape = item
+        .and_then(|item| object_shape(components, item, &mut vec![name.to_string()]))
+        .filter(|shape| !shape.properties.is_empty() || !shape.flattened.is_empty());
+    let (alias, fields) = match shape {
+        Some(shape) => {
+            let mut props: Vec<(ModelField, _)> = Vec::new();
+            for (wire, prop) in &shape.properties {
+                let (field_name, rename) =
+                    unique_field(wire, |n| props.iter().any(|(f, _)| f.name == n));
+                let ty = match inline_enum(prop) {
+                    Some((enum_schema, is_array)) => {
+                        let enum_name = inline_name(&type_name, wire, taken, &inline);
+                        let model = enum_model(&enum_name, &module, enum_schema)
+                            .expect("inline_enum only returns string enums");
+                        inline.push(model);
+                        if is_array {
+                            format!("Vec<{enum_name}>")
+                        } else {
+                            enum_name
+                        }
+                    }
+                    None => match inline_union(prop) {
+                        Some(union_schema) => {
+                            let union_name = inline_name(&type_name, wire, taken, &inline);
+                            let model =
+                                union_model(&union_name, &module, union_schema, &mut uses, map)
+                                    .expect("inline_union only returns unions");
+                            inline.push(model);
+                            union_name
+                        }
+                        None => rust_type(prop, &mut uses, map),
+                    },
+                };
+                let data = match prop {
+                    ReferenceOr::Item(prop) => Some(&prop.schema_data),
+                    ReferenceOr::Reference { .. } => None,
+                };
+                let write_only = data.is_some_and(|d| d.write_only);
+                let read_only = data.is_some_and(|d| d.read_only);
+                let field = ModelField {
+                    name: field_name,
+                    rename,
+                    ty,
+                    required: shape.required.contains(wire) && !write_only,
+                    flatten: false,
+                    nullable: data.is_some_and(|d| d.nullable),
+                    read_only,
+                    write_only,
+                    // The server fills read-only fields, whatever they hold.
+                    constraints: match read_only {
+                        true => Vec::new(),
+                        false => constraints(components, prop, map),
+                    },
+                    nested: false,
+                };
+                props.push((field, prop));
+            }
+
+            let mut fields: Vec<ModelField> = Vec::new();
+            for base in &shape.flattened {
+                let reference = format!("{SCHEMA_REF_PREFIX}{base}");
+                let (base, _) = field_ident(base);
+                fields.push(ModelField {
+                    name: unique_ident(&base, |n| {
+                        fields
+                            .iter()
+                            .chain(props.iter().map(|(f, _)| f))
+                            .any(|f| f.name == n)
+                    }),
+                    rename: None,
+                    ty: rust_type(
+                        &ReferenceOr::<Schema>::Reference { reference },
+                        &mut uses,
+                        map,
+                    ),
+                    required: true,
+                    flatten: true,
+                    nullable: false,
+                    read_only: false,
+                    write_only: false,
+                    constraints: Vec::new(),
+                    nested: false,
+                });
+            }
+            fields.extend(group_exclusive_fields(
+                &type_name,
+                &module,
+                props,
+                taken,
+                &mut inline,
+            ));
+            if let Some(additional) = &shape.additional {
+                fields.push(ModelField {
+                    name: unique_ident("extra", |n| fields.iter().any(|f| f.name == n)),
+                    rename: None,
+                    ty: map_value_type(additional, &mut uses, map),
+                    required: true,
+                    flatten: true,
+                    nullable: false,
+                    read_only: false,
+                    write_only: false,
+                    constraints: Vec::new(),
+                    nested: false,
+                });
+            }
+            (None, fields)
+        }
+        None => (Some(rust_type(schema, &mut uses, map)), Vec::new()),
+    };
+
+    // A model never needs to import itself.
+    uses.retain(|u| u.ty != type_name);
+
+    Model {
+        module,
+        name: type_name,
+        alias,
+        fields,
+        inline,
+        model_uses: uses.into_iter().collect(),
+        ..Model::default()
+    }
+}
+
+/// Replaces properties sharing a Scaleway `x-one-of` group, which are
+/// mutually exclusive, with one flattened enum field holding whichever of
+/// them is set. The field takes the position of the group's first property.
+fn group_exclusive_fields(
+    owner: &str,
+    module: &str,
+    props: Vec<(ModelField, &ReferenceOr<Box<Schema>>)>,
+    taken: &HashSet<String>,
+    inline: &mut Vec<Model>,
+) -> Vec<ModelField> {
+    let group_of = |prop: &ReferenceOr<Box<Schema>>| match prop {
+        ReferenceOr::Item(prop) => prop
+            .schema_data
+            .extensions
+            .get("x-one-of")
+            .and_then(|g| g.as_str())
+            .map(str::to_string),
+        ReferenceOr::Reference { .. } => None,
+    };
+    let mut sizes: HashMap<String, usize> = HashMap::new();
+    for (_, prop) in &props {
+        if let Some(group) = group_of(prop) {
+            *sizes.entry(group).or_default() += 1;
+        }
+    }
+
+    let mut fields: Vec<ModelField> = Vec::new();
+    let mut unions: HashMap<String, usize> = HashMap::new();
+    for (field, prop) in props {
+        let Some(group) = group_of(prop).filter(|g| sizes[g] > 1) else {
+            fields.push(field);
+            continue;
+        };
+
+        let index = match unions.get(&group) {
+            Some(&index) => index,
+            None => {
+                let name = inline_name(owner, &group, taken, inline);
+                inline.push(Model {
+                    name: name.clone(),
+                    module: module.to_string(),
+                    exclusive: true,
+                    ..Model::default()
+                });
+                let base = field_ident(&group).0;
+                fields.push(ModelField {
+                    name: unique_ident(&base, |n| fields.iter().any(|f| f.name == n)),
+                    rename: None,
+                    ty: format!("Option<{name}>"),
+                    required: true,
+                    flatten: true,
+                    nullable: false,
+                    read_only: false,
+                    write_only: false,
+                    constraints: Vec::new(),
+                    nested: false,
+                });
+                unions.insert(group, inline.len() - 1);
+                inline.len() - 1
+            }
+        };
+
+        let variants = &mut inline[index].variants;
+        let base = variant_ident(field.wire_name());
+        let description_lines = match prop {
+            ReferenceOr::Item(prop) => prop
+                .schema_data
+                .description
+                .as_deref()
+                .map(doc_lines)
+                .unwrap_or_default(),
+            ReferenceOr::Reference { .. } => Vec::new(),
+        };
+        variants.push(EnumVariant {
+            name: unique_ident(&base, |n| variants.iter().any(|v| v.name == n)),
+            literal: Some(format!("{:?}", field.wire_name())),
+            ty: Some(field.ty),
+            description_lines,
+            nested: false,
+        });
+    }
+
+    fields
+}
+
+/// Builds an enum for a `oneOf`/`anyOf` schema with more than one member.
+///
+/// A discriminator makes it internally tagged, with one variant per mapping
+/// entry or, without a mapping, per referenced member named after its
+/// schema. Everything else is untagged and matched in declaration order.
+fn union_model(
+    name: &str,
+    module: &str,
+    schema: &Schema,
+    uses: &mut BTreeSet<ModelUse>,
+    map: MapType,
+) -> Option<Model> {
+    let members = match &schema.schema_kind {
+        SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members }
+            if members.len() > 1 =>
+        {
+            members
+        }
+        _ => return None,
+    };
+
+    let mut variants: Vec<EnumVariant> = Vec::new();
+    let mut push = |base: String, literal: Option<String>, ty: String| {
+        let name = unique_ident(&base, |n| variants.iter().any(|v| v.name == n));
+        variants.push(EnumVariant {
+            name,
+            literal,
+            ty: Some(ty),
+            description_lines: Vec::new(),
+            nested: false,
+        });
+    };
+
+    let discriminator = schema.schema_data.discriminator.as_ref();
+    let mapping: Option<Vec<(String, String)>> = discriminator.and_then(|d| {
+        if !d.mapping.is_empty() {
+            return Some(
+                d.mapping
+                    .iter()
+                    .map(|(k, v)| {
+                        // Mappings may name a schema instead of referring to it.
+                        let reference = if v.contains('/') {
+                            v.clone()
+                        } else {
+                            format!("{SCHEMA_REF_PREFIX}{v}")
+                        };
+                        (k.clone(), reference)
+                    })
+                    .collect(),
+            );
+        }
+        members
+            .iter()
+            .map(|m| match m {
+                ReferenceOr::Reference { reference } => {
+                    let value = reference.strip_prefix(SCHEMA_REF_PREFIX)?;
+                    Some((value.to_string(), reference.clone()))
+                }
+                ReferenceOr::Item(_) => None,
+            })
+            .collect()
+    });
+
+    let tag = match (discriminator, mapping) {
+        (Some(discriminator), Some(mapping)) => {
+            for (value, reference) in mapping {
+                let ty = rust_type(&ReferenceOr::<Schema>::Reference { reference }, uses, map);
+                push(variant_ident(&value), Some(format!("{value:?}")), ty);
+            }
+            Some(format!("{:?}", discriminator.property_name))
+        }
+        _ => {
+            for member in members {
+                let ty = rust_type(member, uses, map);
+                push(variant_name(member), None, ty);
+            }
+            None
+        }
+    };
+
+    Some(Model {
+        name: name.to_string(),
+        module: module.to_string(),
+        untagged: tag.is_none(),
+        tag,
+        variants,
+        ..Model::default()
+    })
+}
+
+/// Names an untagged union variant after the member's schema name, or after
+/// the kind of value it holds when the member is inline.
+fn variant_name(member: &ReferenceOr<Schema>) -> String {
+    let schema = match member {
+        ReferenceOr::Reference { reference } => {
+            return variant_ident(reference.rsplit('/').next().unwrap_or_default());
+        }
+        ReferenceOr::Item(schema) => schema,
+    };
+
+    let kind = match &schema.schema_kind {
+        SchemaKind::Type(Type::String(_)) => "String",
+        SchemaKind::Type(Type::Integer(_)) => "Integer",
+        SchemaKind::Type(Type::Number(_)) => "Number",
+        SchemaKind::Type(Type::Boolean(_)) => "Boolean",
+        SchemaKind::Type(Type::Array(_)) => "Array",
+        SchemaKind::Type(Type::Object(_)) => "Object",
+        SchemaKind::Any(any) => match any.typ.as_deref() {
+            Some("string") => "String",
+            Some("integer") => "Integer",
+            Some("number") => "Number",
+            Some("boolean") => "Boolean",
+            Some("array") => "Array",
+            Some("object") => "Object",
+            _ if !any.properties.is_empty() => "Object",
+            _ => "Value",
+        },
+        _ => "Value",
+    };
+    kind.to_string()
+}
+
+/// Builds a string enum model when `schema` lists the values it accepts.
+fn enum_model(name: &str, module: &str, schema: &Schema) -> Option<Model> {
+    let SchemaKind::Type(Type::String(string)) = &schema.schema_kind else {
+        return None;
+    };
+    let values: Vec<&String> = string.enumeration.iter().flatten().collect();
+    if values.is_empty() {
+        return None;
+    }
+
+    // Scaleway documents each value under `x-enum-descriptions.values`.
+    let descriptions = schema
+        .schema_data
+        .extensions
+        .get("x-enum-descriptions")
+        .and_then(|d| d.get("values"));
+
+    let mut variants: Vec<EnumVariant> = Vec::new();
+    for value in values {
+        let literal = format!("{value:?}");
+        if variants
+            .iter()
+            .any(|v| v.literal.as_ref() == Some(&literal))
+        {
+            continue;
+        }
+        let base = variant_ident(value);
+        let name = unique_ident(&base, |n| variants.iter().any(|v| v.name == n));
+        variants.push(EnumVariant {
+            name,
+            literal: Some(format!("{value:?}")),
+            ty: None,
+            description_lines: descriptions
+                .and_then(|d| d.get(value.as_str()))
+                .and_then(|d| d.as_str())
+                .map(doc_lines)
+                .unwrap_or_default(),
+            nested: false,
+        });
+    }
+
+    let catch_all = unique_ident("Unknown", |n| variants.iter().any(|v| v.name == n));
+    Some(Model {
+        name: name.to_string(),
+        module: module.to_string(),
+        variants,
+        catch_all: Some(catch_all),
+        ..Model::default()
+    })
+}
+
+/// Returns the inline string enum declared by a property, either directly or
+/// as the item type of an array, and whether it is an array.
+fn inline_enum(prop: &ReferenceOr<Box<Schema>>) -> Option<(&Schema, bool)> {
+    let ReferenceOr::Item(schema) = prop else {
+        return None;
+    };
+    let is_enum = |s: &Schema| {
+        matches!(&s.schema_kind, SchemaKind::Type(Type::String(string))
+            if string.enumeration.iter().any(Option::is_some))
+    };
+
+    if is_enum(schema) {
+        return Some((schema, false));
+    }
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Array(arr)) => match arr.items.as_ref()? {
+            ReferenceOr::Item(items) if is_enum(items) => Some((items, true)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the inline `oneOf`/`anyOf` union declared by a property.
+fn inline_union(prop: &ReferenceOr<Box<Schema>>) -> Option<&Schema> {
+    match prop {
+        ReferenceOr::Item(schema) => match &schema.schema_kind {
+            SchemaKind::OneOf { one_of: members } | SchemaKind::AnyOf { any_of: members }
+                if members.len() > 1 =>
+            {
+                Some(schema)
+            }
+            _ => None,
+        },
+        ReferenceOr::Reference { .. } => None,
+    }
+}
+
+/// Names a type synthesized for the property `wire` of `owner`, e.g.
+/// `DropletStatus`, avoiding component names and earlier inline types.
+fn inline_name(owner: &str, wire: &str, taken: &HashSet<String>, inline: &[Model]) -> String {
+    let base = format!("{owner}{}", to_pascal_case(wire).trim_start_matches('_'));
+    unique_ident(&base, |n| {
+        taken.contains(n) || inline.iter().any(|m| m.name == n)
+    })
+}
+
+/// The properties of `schema` with every `allOf` member merged in, `None`
+/// when it is not an object.
+pub(crate) fn object_properties(
+    components: &Components,
+    schema: &Schema,
+) -> Option<Vec<(String, ReferenceOr<Box<Schema>>)>> {
+    Some(object_shape(components, schema, &mut Vec::new())?.properties)
+}
+
+/// The properties of an object schema with every `allOf` member merged in.
+#[derive(Debug, Default)]
+struct ObjectShape {
+    properties: Vec<(String, ReferenceOr<Box<Schema>>)>,
+    required: Vec<String>,
+    /// Named `allOf` members that are maps rather than objects with
+    /// properties, kept as `#[serde(flatten)]` fields.
+    flattened: Vec<String>,
+    /// Properties beyond the named ones, collected into a flattened map.
+    additional: Option<AdditionalProperties>,
+}
+
+impl ObjectShape {
+    /// Adds `other` on top of `self`; later properties win.
+    fn merge(&mut self, other: ObjectShape) {
+        for (name, prop) in other.properties {
+            match self.properties.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, existing)) => *existing = prop,
+                None => self.properties.push((name, prop)),
+            }
+        }
+        for name in other.required {
+            if !self.required.contains(&name) {
+                self.required.push(name);
+            }
+        }
+        for name in other.flattened {
+            if !self.flattened.contains(&name) {
+                self.flattened.push(name);
+            }
+        }
+        if other.additional.is_some() {
+            self.additional = other.additional;
+        }
+    }
+}
+
+/// `additionalProperties: false` is the same as leaving it out.
+fn open_properties(additional: Option<&AdditionalProperties>) -> Option<&AdditionalProperties> {
+    additional.filter(|a| !matches!(a, AdditionalProperties::Any(false)))
+}
+
+/// The map holding the undeclared properties of an object, keyed by name.
+fn map_value_type(
+    additional: &AdditionalProperties,
+    uses: &mut BTreeSet<ModelUse>,
+    map: MapType,
+) -> String {
+    let value = match additional {
+        AdditionalProperties::Schema(schema) => rust_type(schema, uses, map),
+        AdditionalProperties::Any(_) => "serde_json::Value".to_string(),
+    };
+    format!("{}<String, {value}>", map.path())
+}
+
+/// The constraints a schema puts on its values, whatever its type.
+#[derive(Debug, Default)]
+struct Bounds<'a> {
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    pattern: Option<&'a str>,
+    /// Bound and whether it is exclusive.
+    minimum: Option<(f64, bool)>,
+    maximum: Option<(f64, bool)>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    unique_items: bool,
+    /// String enums become enum types, which are checked when deserializing.
+    enumerated: bool,
+}
+
+/// The `crate::validation` checks of a property, following refs to the
+/// component declaring them.
+fn constraints(
+    components: &Components,
+    prop: &ReferenceOr<Box<Schema>>,
+    map: MapType,
+) -> Vec<Constraint> {
+    let Some(schema) = constrained_schema(components, prop) else {
+        return Vec::new();
+    };
+    let ty = schema_type(schema, &mut BTreeSet::new(), map);
+    let bounds = match &schema.schema_kind {
+        SchemaKind::Type(Type::String(string)) => Bounds {
+            min_length: string.min_length,
+            max_length: string.max_length,
+            pattern: string.pattern.as_deref(),
+            enumerated: !string.enumeration.is_empty(),
+            ..Bounds::default()
+        },
+        SchemaKind::Type(Type::Integer(int)) => Bounds {
+            minimum: int.minimum.map(|min| (min as f64, int.exclusive_minimum)),
+            maximum: int.maximum.map(|max| (max as f64, int.exclusive_maximum)),
+            ..Bounds::default()
+        },
+        SchemaKind::Type(Type::Number(num)) => Bounds {
+            minimum: num.minimum.map(|min| (min, num.exclusive_minimum)),
+            maximum: num.maximum.map(|max| (max, num.exclusive_maximum)),
+            ..Bounds::default()
+        },
+        SchemaKind::Type(Type::Array(arr)) => Bounds {
+            min_items: arr.min_items,
+            max_items: arr.max_items,
+            unique_items: arr.unique_items,
+            ..Bounds::default()
+        },
+        SchemaKind::Any(any) => Bounds {
+            min_length: any.min_length,
+            max_length: any.max_length,
+            pattern: any.pattern.as_deref(),
+            minimum: any
+                .minimum
+                .map(|min| (min, any.exclusive_minimum.unwrap_or_default())),
+            maximum: any
+                .maximum
+                .map(|max| (max, any.exclusive_maximum.unwrap_or_default())),
+            min_items: any.min_items,
+            max_items: any.max_items,
+            unique_items: any.unique_items.unwrap_or_default(),
+            enumerated: !any.enumeration.is_empty(),
+        },
+        _ => return Vec::new(),
+    };
+
+    let mut checks = Vec::new();
+    let mut check = |check: &'static str, args: String| checks.push(Constraint { check, args });
+    // Formatted strings, e.g. dates, are parsed into types of their own.
+    if ty == "String" && !bounds.enumerated {
+        if let Some(min) = bounds.min_length.filter(|&min| min > 0) {
+            check("min_length", min.to_string());
+        }
+        if let Some(max) = bounds.max_length {
+            check("max_length", max.to_string());
+        }
+        match bounds.pattern {
+            Some(pattern) if regex::Regex::new(pattern).is_ok() => {
+                check("pattern", format!("{pattern:?}"));
+            }
+            Some(pattern) => tracing::debug!(pattern, "skipping unsupported pattern"),
+            None => {}
+        }
+    }
+    for (name, bound) in [("minimum", bounds.minimum), ("maximum", bounds.maximum)] {
+        let Some((value, exclusive)) = bound else {
+            continue;
+        };
+        if let Some(literal) = bound_literal(&ty, value) {
+            check(name, format!("{literal}, {exclusive}"));
+        }
+    }
+    if ty.starts_with("Vec<") {
+        if let Some(min) = bounds.min_items.filter(|&min| min > 0) {
+            check("min_items", min.to_string());
+        }
+        if let Some(max) = bounds.max_items {
+            check("max_items", max.to_string());
+        }
+        if bounds.unique_items {
+            check("unique_items", String::new());
+        }
+    }
+    checks
+}
+
+/// The schema a property's constraints are read from: its own, the component
+/// it refers to or the single member of an `allOf` wrapping either.
+fn constrained_schema<'a>(
+    components: &'a Components,
+    prop: &'a ReferenceOr<Box<Schema>>,
+) -> Option<&'a Schema> {
+    let lookup = |reference: &str| match components
+        .schemas
+        .get(reference.strip_prefix(SCHEMA_REF_PREFIX)?)?
+    {
+        ReferenceOr::Item(schema) => Some(schema),
+        ReferenceOr::Reference { .. } => None,
+    };
+
+    let mut schema = match prop {
+        ReferenceOr::Item(schema) => schema.as_ref(),
+        ReferenceOr::Reference { reference } => lookup(reference)?,
+    };
+    // Bounded so that an `allOf` referring to itself cannot loop forever.
+    for _ in 0..16 {
+        schema = match &schema.schema_kind {
+            SchemaKind::AllOf { all_of } if all_of.len() == 1 => match &all_of[0] {
+                ReferenceOr::Item(member) => member,
+                ReferenceOr::Reference { reference } => lookup(reference)?,
+            },
+            _ => return Some(schema),
+        };
+    }
+    None
+}
+
+/// Renders a numeric bound as a literal of `ty`, or `None` when `ty` cannot
+/// hold it, e.g. a negative minimum of an unsigned integer.
+fn bound_literal(ty: &str, bound: f64) -> Option<String> {
+    let (min, max) = match ty {
+        "f32" | "f64" => return Some(format!("{bound:?}")),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" => (i64::MIN as i128, i64::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" => (0, u64::MAX as i128),
+        _ => return None,
+    };
+    let value = bound as i128;
+    (bound.fract() == 0.0 && (min..=max).contains(&value)).then(|| value.to_string())
+}
+
+/// Describes `schema` as an object, merging `allOf` members that are objects
+/// themselves. `None` when a member is neither an object nor a map, e.g. a
+/// string enum wrapped to attach a description, which is an alias instead.
+/// `seen` holds the component names being merged, outermost first, so a
+/// member referring back to one of them, adding nothing new, is skipped.
+fn object_shape(
+    components: &Components,
+    schema: &Schema,
+    seen: &mut Vec<String>,
+) -> Option<ObjectShape> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => Some(ObjectShape {
+            properties: obj.properties.clone().into_iter().collect(),
+            required: obj.required.clone(),
+            flattened: Vec::new(),
+            additional: open_properties(obj.additional_properties.as_ref()).cloned(),
+        }),
+        // `type: object` next to `allOf` only parses as an untyped schema.
+        SchemaKind::Any(any)
+            if matches!(any.typ.as_deref(), None | Some("object"))
+                && (!any.properties.is_empty()
+                    || !any.required.is_empty()
+                    || !any.all_of.is_empty()) =>
+        {
+            let mut shape = ObjectShape::default();
+            merge_all_of(components, &any.all_of, &mut shape, seen)?;
+            shape.merge(ObjectShape {
+                properties: any.properties.clone().into_iter().collect(),
+                required: any.required.clone(),
+                flattened: Vec::new(),
+                additional: open_properties(any.additional_properties.as_ref()).cloned(),
+            });
+            Some(shape)
+        }
+        SchemaKind::AllOf { all_of } => {
+            let mut shape = ObjectShape::default();
+            merge_all_of(components, all_of, &mut shape, seen)?;
+            Some(shape)
+        }
+        _ => None,
+    }
+}
+
+fn merge_all_of(
+    components: &Components,
+    members: &[ReferenceOr<Schema>],
+    shape: &mut ObjectShape,
+    seen: &mut Vec<String>,
+) -> Option<()> {
+    for member in members {
+        match member {
+            ReferenceOr::Reference { reference } => {
+                let name = reference.strip_prefix(SCHEMA_REF_PREFIX)?;
+                let ReferenceOr::Item(target) = components.schemas.get(name)? else {
+                    return None;
+                };
+
+                if seen.iter().any(|s| s == name) {
+                    continue;
+                }
+                seen.push(name.to_string());
+                let member = object_shape(components, target, seen);
+                seen.pop();
+
+                match member {
+                    Some(member) => shape.merge(member),
+                    None if is_map(target) => shape.flattened.push(name.to_string()),
+                    None => return None,
+                }
+            }
+            ReferenceOr::Item(member)
+                if is_annotation(member) || is_alternative_constraint(member) => {}
+            ReferenceOr::Item(member) => shape.merge(object_shape(components, member, seen)?),
+        }
+    }
+    Some(())
+}
+
+/// Schemas holding nothing but undeclared properties, generated as maps.
+fn is_map(schema: &Schema) -> bool {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => obj.properties.is_empty(),
+        SchemaKind::Any(any) => {
+            matches!(any.typ.as_deref(), None | Some("object"))
+                && any.properties.is_empty()
+                && open_properties(any.additional_properties.as_ref()).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// `anyOf`/`oneOf` members that only choose between sets of required
+/// properties, e.g. "inbound or outbound rules", constrain the payload
+/// without changing its shape.
+fn is_alternative_constraint(schema: &Schema) -> bool {
+    match &schema.schema_kind {
+        SchemaKind::AnyOf { any_of: members } | SchemaKind::OneOf { one_of: members } => {
+            members.iter().all(|m| match m {
+                ReferenceOr::Item(Schema {
+                    schema_kind: SchemaKind::Any(any),
+                    ..
+                }) => any.typ.is_none() && any.properties.is_empty(),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Members that only carry a description or example, commonly placed next to
+/// a `$ref` in `allOf`, add nothing to the type.
+pub(crate) fn is_annotation(schema: &Schema) -> bool {
+    match &schema.schema_kind {
+        SchemaKind::Any(any) => {
+            any.typ.is_none()
+                && any.properties.is_empty()
+                && any.required.is_empty()
+                && any.additional_properties.is_none()
+                && any.items.is_none()
+                && any.enumeration.is_empty()
+                && any.format.is_none()
+                && any.all_of.is_empty()
+                && any.one_of.is_empty()
+                && any.any_of.is_empty()
+                && any.not.is_none()
+        }
+        _ => false,
+    }
+}
+
+/// Maps a schema (or a reference to one) to the Rust type used in generated
+/// code, recording every model that needs to be imported into `uses`.
+pub(crate) fn rust_type<S: Borrow<Schema>>(
+    schema: &ReferenceOr<S>,
+    uses: &mut BTreeSet<ModelUse>,
+    map: MapType,
+) -> String {
+    match schema {
+        ReferenceOr::Reference { reference } => match reference.strip_prefix(SCHEMA_REF_PREFIX) {
+            Some(name) => {
+                let ty = type_ident(name);
+                uses.insert(ModelUse {
+                    module: module_ident(name),
+                    ty: ty.clone(),
+                });
+                ty
+            }
+            None => "serde_json::Value".to_string(),
+        },
+        ReferenceOr::Item(item) => schema_type(item.borrow(), uses, map),
+    }
+}
+
+fn schema_type(schema: &Schema, uses: &mut BTreeSet<ModelUse>, map: MapType) -> String {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(string)) => match &string.format {
+            VariantOrUnknownOrEmpty::Item(format) => string_format_type(format).to_string(),
+            VariantOrUnknownOrEmpty::Unknown(format) => unknown_format_type(format).to_string(),
+            VariantOrUnknownOrEmpty::Empty => "String".to_string(),
+        },
+        SchemaKind::Type(Type::Integer(int)) => match &int.format {
+            VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => "i32".to_string(),
+            VariantOrUnknownOrEmpty::Unknown(format) => integer_format_type(format).to_string(),
+            _ => "i64".to_string(),
+        },
+        SchemaKind::Type(Type::Number(num)) => match num.format {
+            VariantOrUnknownOrEmpty::Item(NumberFormat::Float) => "f32".to_string(),
+            _ => "f64".to_string(),
+        },
+        SchemaKind::Type(Type::Boolean(_)) => "bool".to_string(),
+        SchemaKind::Type(Type::Object(obj)) if obj.properties.is_empty() => {
+            free_form_type(obj.additional_properties.as_ref(), uses, map)
+        }
+        SchemaKind::Type(Type::Array(arr)) => match &arr.items {
+            Some(items) => format!("Vec<{}>", rust_type(items, uses, map)),
+            None => "Vec<serde_json::Value>".to_string(),
+        },
+        // Single member compositions are commonly used to attach a description
+        // to a reference, so they map straight to the member type.
+        SchemaKind::AllOf { all_of: members }
+        | SchemaKind::OneOf { one_of: members }
+        | SchemaKind::AnyOf { any_of: members }
+            if members.len() == 1 =>
+        {
+            rust_type(&members[0], uses, map)
+        }
+        SchemaKind::AllOf { all_of } => {
+            let mut typed = all_of.iter().filter(|m| match m {
+                ReferenceOr::Item(member) => !is_annotation(member),
+                ReferenceOr::Reference { .. } => true,
+            });
+            match (typed.next(), typed.next()) {
+                (Some(member), None) => rust_type(member, uses, map),
+                _ => "serde_json::Value".to_string(),
+            }
+        }
+        SchemaKind::Any(any) => match any.typ.as_deref() {
+            Some("string") => any
+                .format
+                .as_deref()
+                .map_or("String", unknown_format_type)
+                .to_string(),
+            // Bounds beyond `i64` leave a schema untyped, e.g. DigitalOcean's
+            // `maximum: 18446744073709552000`.
+            Some("integer") if any.maximum.is_some_and(|max| max > i64::MAX as f64) => {
+                "u64".to_string()
+            }
+            Some("integer") => any
+                .format
+                .as_deref()
+                .map_or("i64", integer_format_type)
+                .to_string(),
+            Some("number") => "f64".to_string(),
+            Some("boolean") => "bool".to_string(),
+            Some("array") => match &any.items {
+                Some(items) => format!("Vec<{}>", rust_type(items, uses, map)),
+                None => "Vec<serde_json::Value>".to_string(),
+            },
+            None | Some("object") if any.properties.is_empty() => {
+                free_form_type(any.additional_properties.as_ref(), uses, map)
+            }
+            _ => "serde_json::Value".to_string(),
+        },
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+/// Objects without named properties: typed maps for `additionalProperties`
+/// schemas, a JSON object for `additionalProperties: true` and any JSON
+/// value otherwise.
+fn free_form_type(
+    additional: Option<&AdditionalProperties>,
+    uses: &mut BTreeSet<ModelUse>,
+    map: MapType,
+) -> String {
+    match open_properties(additional) {
+        Some(AdditionalProperties::Schema(schema)) => {
+            format!("{}<String, {}>", map.path(), rust_type(schema, uses, map))
+        }
+        Some(AdditionalProperties::Any(_)) => {
+            "serde_json::Map<String, serde_json::Value>".to_string()
+        }
+        None => "serde_json::Value".to_string(),
+    }
+}
+
+fn string_format_type(format: &StringFormat) -> &'static str {
+    match format {
+        StringFormat::DateTime => "crate::types::DateTime",
+        StringFormat::Date => "crate::types::Date",
+        StringFormat::Byte => "crate::types::Base64",
+        StringFormat::Binary => "bytes::Bytes",
+        StringFormat::Password => "String",
+    }
+}
+
+/// Maps string formats `openapiv3` does not model, named as in the spec.
+fn unknown_format_type(format: &str) -> &'static str {
+    match format {
+        "date-time" => "crate::types::DateTime",
+        "date" => "crate::types::Date",
+        "byte" => "crate::types::Base64",
+        "binary" => "bytes::Bytes",
+        "uuid" => "crate::types::Uuid",
+        _ => "String",
+    }
+}
+
+fn integer_format_type(format: &str) -> &'static str {
+    match format {
+        "int32" => "i32",
+        "uint32" => "u32",
+        "uint64" => "u64",
+        _ => "i64",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn components(schemas: serde_json::Value) -> Components {
+        serde_json::from_value(json!({ "schemas": schemas })).unwrap()
+    }
+
+    fn shape(components: &Components, name: &str) -> Option<ObjectShape> {
+        let ReferenceOr::Item(schema) = &components.schemas[name] else {
+            panic!("{name} is a reference");
+        };
+        object_shape(components, schema, &mut vec![name.to_string()])
+    }
+
+    fn property_names(shape: &ObjectShape) -> Vec<&str> {
+        shape.properties.iter().map(|(n, _)| n.as_str()).collect()
+    }
+
+    #[test]
+    fn merges_object_members() {
+        let components = components(json!({
+            "Base": {
+                "type": "object",
+                "required": ["id"],
+                "properties": { "id": { "type": "string" }, "name": { "type": "string" } },
+            },
+            "Droplet": {
+                "allOf": [
+                    { "$ref": "#/components/schemas/Base" },
+                    { "description": "only documentation" },
+                    {
+                        "type": "object",
+                        "required": ["id", "size"],
+                        "properties": { "name": { "type": "integer" }, "size": { "type": "string" } },
+                    },
+                ],
+            },
+        }));
+        let shape = shape(&components, "Droplet").unwrap();
+
+        assert_eq!(property_names(&shape), ["id", "name", "size"]);
+        assert_eq!(shape.required, ["id", "size"]);
+        assert!(shape.flattened.is_empty());
+        // Later members win.
+        let name = &shape.properties[1].1;
+        assert_eq!(
+            rust_type(name, &mut BTreeSet::new(), MapType::default()),
+            "i64"
+        );
+    }
+
+    #[test]
+    fn merges_properties_next_to_all_of() {
+        let components = components(json!({
+            "Base": { "type": "object", "properties": { "id": { "type": "string" } } },
+            "Server": {
+                "type": "object",
+                "allOf": [{ "$ref": "#/components/schemas/Base" }],
+                "properties": { "ipv6": { "type": "string" } },
+                "additionalProperties": { "type": "string" },
+            },
+        }));
+        let shape = shape(&components, "Server").unwrap();
+
+        assert_eq!(property_names(&shape), ["id", "ipv6"]);
+        assert!(shape.additional.is_some());
+    }
+
+    #[test]
+    fn skips_recursive_members_and_flattens_maps() {
+        let components = components(json!({
+            "Labels": { "additionalProperties": { "type": "string" } },
+            "Node": {
+                "allOf": [
+                    { "$ref": "#/components/schemas/Labels" },
+                    { "$ref": "#/components/schemas/Node" },
+                    { "type": "object", "properties": { "id": { "type": "string" } } },
+                ],
+            },
+        }));
+        let shape = shape(&components, "Node").unwrap();
+
+        assert_eq!(property_names(&shape), ["id"]);
+        assert_eq!(shape.flattened, ["Labels"]);
+    }
+
+    #[test]
+    fn aliases_non_object_members() {
+        let components = components(json!({
+            "Status": { "type": "string", "enum": ["on", "off"] },
+            "Tagged": { "type": "array", "items": { "type": "string" } },
+            "WrappedStatus": {
+                "allOf": [
+                    { "$ref": "#/components/schemas/Status" },
+                    { "description": "The status of the droplet." },
+                ],
+            },
+            "WrappedTags": { "allOf": [{ "$ref": "#/components/schemas/Tagged" }] },
+            "Mixed": {
+                "allOf": [
+                    { "$ref": "#/components/schemas/Tagged" },
+                    { "type": "object", "properties": { "id": { "type": "string" } } },
+                ],
+            },
+        }));
+        let alias = |name: &str| {
+            let schema = &components.schemas[name];
+            let model = build_model(
+                &components,
+                name,
+                schema,
+                &HashSet::new(),
+                MapType::default(),
+            );
+            assert!(model.fields.is_empty(), "{name}");
+            model.alias
+        };
+
+        assert_eq!(alias("WrappedStatus").as_deref(), Some("Status"));
+        assert_eq!(alias("WrappedTags").as_deref(), Some("Tagged"));
+        assert_eq!(alias("Mixed").as_deref(), Some("serde_json::Value"));
+    }
+
+    #[test]
+    fn ignores_alternative_required_sets() {
+        let components = components(json!({
+            "Rules": {
+                "allOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "inbound": { "type": "array", "items": { "type": "string" } },
+                            "outbound": { "type": "array", "items": { "type": "string" } },
+                        },
+                    },
+                    { "anyOf": [{ "required": ["inbound"] }, { "required": ["outbound"] }] },
+                ],
+            },
+        }));
+        let shape = shape(&components, "Rules").unwrap();
+
+        assert_eq!(property_names(&shape), ["inbound", "outbound"]);
+        assert!(shape.required.is_empty());
+    }
+
+    #[test]
+    fn rejects_inline_non_object_members() {
+        let components = components(json!({
+            "Mixed": { "allOf": [{ "type": "object" }, { "type": "string" }] },
+        }));
+        assert!(shape(&components, "Mixed").is_none());
+        assert_eq!(
+            object_properties(
+                &components,
+                &Schema {
+                    schema_data: Default::default(),
+                    schema_kind: SchemaKind::Type(Type::Boolean(Default::default())),
+                }
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn boxes_recursive_fields() {
+        let spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": { "title": "test", "version": "1" },
+            "paths": {},
+            "components": { "schemas": {
+                "A": { "type": "object", "properties": { "b": { "$ref": "#/components/schemas/B" } } },
+                "B": {
+                    "type": "object",
+                    "required": ["a"],
+                    "properties": { "a": { "$ref": "#/components/schemas/A" } },
+                },
+                "Leaf": { "type": "object", "properties": { "id": { "type": "string" } } },
+                "Node": {
+                    "type": "object",
+                    "required": ["leaf"],
+                    "properties": {
+                        "children": { "type": "array", "items": { "$ref": "#/components/schemas/Node" } },
+                        "leaf": { "$ref": "#/components/schemas/Leaf" },
+                        "next": { "$ref": "#/components/schemas/Node" },
+                        "parent": { "$ref": "#/components/schemas/Node", "nullable": true },
+                    },
+                },
+            } },
+        }))
+        .unwrap();
+        let models = build_models(&spec, MapType::default());
+        let types = |name: &str| -> Vec<(String, String)> {
+            let model = models.iter().find(|m| m.name == name).unwrap();
+            model
+                .fields
+                .iter()
+                .map(|f| (f.name.clone(), f.ty.clone()))
+                .collect()
+        };
+        let field = |name: &str, ty: &str| (name.to_string(), ty.to_string());
+
+        assert_eq!(
+            types("Node"),
+            [
+                field("children", "Vec<Node>"),
+                field("leaf", "Leaf"),
+                field("next", "Box<Node>"),
+                field("parent", "Box<Node>"),
+            ]
+        );
+        assert_eq!(types("A"), [field("b", "Box<B>")]);
+        assert_eq!(types("B"), [field("a", "Box<A>")]);
+        assert_eq!(types("Leaf"), [field("id", "String")]);
+    }
+
+    #[test]
+    fn enums_fall_back_to_unknown() {
+        let components = components(json!({
+            "Status": { "type": "string", "enum": ["on", "off", "on"] },
+            "Outcome": { "type": "string", "enum": ["known", "unknown"] },
+        }));
+        let variants = |name: &str| {
+            let ReferenceOr::Item(schema) = &components.schemas[name] else {
+                panic!("{name} is a reference");
+            };
+            let model = enum_model(name, "m", schema).unwrap();
+            let names: Vec<String> = model.variants.iter().map(|v| v.name.clone()).collect();
+            (names, model.catch_all.unwrap())
+        };
+
+        assert_eq!(
+            variants("Status"),
+            (
+                vec!["On".to_string(), "Off".to_string()],
+                "Unknown".to_string()
+            )
+        );
+        // A listed `unknown` value keeps its name; the fallback moves aside.
+        assert_eq!(
+            variants("Outcome"),
+            (
+                vec!["Known".to_string(), "Unknown".to_string()],
+                "Unknown2".to_string()
+            )
+        );
+    }
+
+    fn union(components: &Components, name: &str) -> Model {
+        let ReferenceOr::Item(schema) = &components.schemas[name] else {
+            panic!("{name} is a reference");
+        };
+        union_model(name, "m", schema, &mut BTreeSet::new(), MapType::default()).unwrap()
+    }
+
+    fn variants(model: &Model) -> Vec<(&str, Option<&str>, Option<&str>)> {
+        model
+            .variants
+            .iter()
+            .map(|v| (v.name.as_str(), v.literal.as_deref(), v.ty.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn tags_unions_by_discriminator_mapping() {
+        let components = components(json!({
+            "Number": { "type": "object", "properties": { "number": { "type": "number" } } },
+            "Text": { "type": "object", "properties": { "text": { "type": "string" } } },
+            "Value": {
+                "oneOf": [
+                    { "$ref": "#/components/schemas/Text" },
+                    { "$ref": "#/components/schemas/Number" },
+                ],
+                "discriminator": {
+                    "propertyName": "kind",
+                    "mapping": { "plain": "Text", "number": "#/components/schemas/Number" },
+                },
+            },
+            "Unmapped": {
+                "oneOf": [
+                    { "$ref": "#/components/schemas/Text" },
+                    { "$ref": "#/components/schemas/Number" },
+                ],
+                "discriminator": { "propertyName": "kind" },
+            },
+        }));
+
+        let value = union(&components, "Value");
+        assert_eq!(value.tag.as_deref(), Some("\"kind\""));
+        assert!(!value.untagged);
+        assert_eq!(
+            variants(&value),
+            [
+                ("Number", Some("\"number\""), Some("Number")),
+                ("Plain", Some("\"plain\""), Some("Text")),
+            ]
+        );
+
+        let unmapped = union(&components, "Unmapped");
+        assert_eq!(
+            variants(&unmapped),
+            [
+                ("Text", Some("\"Text\""), Some("Text")),
+                ("Number", Some("\"Number\""), Some("Number")),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_unions_without_discriminator_untagged() {
+        let components = components(json!({
+            "Text": { "type": "object", "properties": { "text": { "type": "string" } } },
+            "Value": {
+                "anyOf": [{ "type": "string" }, { "$ref": "#/components/schemas/Text" }],
+            },
+        }));
+
+        let value = union(&components, "Value");
+        assert!(value.untagged);
+        assert!(value.tag.is_none());
+        assert_eq!(
+            variants(&value),
+            [
+                ("String", None, Some("String")),
+                ("Text", None, Some("Text"))
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_x_one_of_properties() {
+        let components = components(json!({
+            "Server": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "password": { "type": "string", "x-one-of": "auth" },
+                    "ssh_key": { "type": "string", "x-one-of": "auth", "description": "A key." },
+                    "zone": { "type": "string", "x-one-of": "placement" },
+                },
+            },
+        }));
+        let schema = &components.schemas["Server"];
+        let model = build_model(
+            &components,
+            "Server",
+            schema,
+            &HashSet::new(),
+            MapType::default(),
+        );
+
+        let fields: Vec<(&str, &str, bool)> = model
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.ty.as_str(), f.flatten))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("name", "String", false),
+                ("auth", "Option<ServerAuth>", true),
+                ("zone", "String", false),
+            ]
+        );
+
+        let [auth] = &model.inline[..] else {
+            panic!("expected one inline model");
+        };
+        assert!(auth.exclusive);
+        assert_eq!(
+            variants(auth),
+            [
+                ("Password", Some("\"password\""), Some("String")),
+                ("SshKey", Some("\"ssh_key\""), Some("String")),
+            ]
+        );
+        assert_eq!(auth.variants[1].description_lines, ["A key."]);
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rust identifiers for names taken from a spec. Wire names are split into
+//! words on separators (`.`, `-`, `@`, spaces, ...) and case changes, then
+//! joined as `snake_case` or `PascalCase` and made safe to use as an
+//! identifier.
+
+use crate::resolver::pointer::escape_token;
+use crate::resolver::resolve::SCHEMA_REF_PREFIX;
+use anyhow::{Context, Result};
+use openapiv3::OpenAPI;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Prelude types that a model of the same name would shadow wherever the
+/// models are glob imported.
+const PRELUDE_TYPES: &[&str] = &["Box", "Option", "Result", "String", "Vec"];
+
+/// Converts a wire name into a Rust field identifier, returning the original
+/// name when a `#[serde(rename)]` is required.
+pub(crate) fn field_ident(wire: &str) -> (String, Option<String>) {
+    unique_field(wire, |_| false)
+}
+
+/// Like [`field_ident`], but adds the first free numeric suffix when the
+/// identifier is already used by another field of the same struct, e.g.
+/// `foo-bar` after `foo_bar`.
+pub(crate) fn unique_field(
+    wire: &str,
+    is_taken: impl Fn(&str) -> bool,
+) -> (String, Option<String>) {
+    let mut ident = to_snake_case(wire);
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if NON_RAW_KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    } else if RUST_KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+    let ident = match unique_ident(&ident, is_taken) {
+        // `r#type2` is no longer a keyword.
+        unique if unique != ident => unique.trim_start_matches("r#").to_string(),
+        unique => unique,
+    };
+
+    let bare = ident.trim_start_matches("r#");
+    let rename = (bare != wire).then(|| wire.to_string());
+
+    (ident, rename)
+}
+
+/// Converts a name into a type identifier. Names of prelude types get a
+/// `Value` suffix, like `Self`.
+pub(crate) fn type_ident(name: &str) -> String {
+    match variant_ident(name) {
+        ident if PRELUDE_TYPES.contains(&ident.as_str()) => format!("{ident}Value"),
+        ident => ident,
+    }
+}
+
+/// Converts a name into an enum variant identifier, which may be named
+/// after a prelude type.
+pub(crate) fn variant_ident(name: &str) -> String {
+    match to_pascal_case(name) {
+        ident if ident.is_empty() => "Empty".to_string(),
+        ident if ident == "Self" => "SelfValue".to_string(),
+        ident => ident,
+    }
+}
+
+/// Converts a name into a module identifier. Keywords get a trailing
+/// underscore instead of the `r#` prefix so the file name stays plain.
+pub(crate) fn module_ident(name: &str) -> String {
+    let mut ident = to_snake_case(name);
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if RUST_KEYWORDS.contains(&ident.as_str()) || NON_RAW_KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+
+    ident
+}
+
+/// Returns `base`, or `base` with the first free numeric suffix.
+pub(crate) fn unique_ident(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    let mut ident = base.to_string();
+    let mut n = 2;
+    while is_taken(&ident) {
+        ident = format!("{base}{n}");
+        n += 1;
+    }
+    ident
+}
+
+pub(crate) fn to_snake_case(name: &str) -> String {
+    words(name).join("_")
+}
+
+pub(crate) fn to_pascal_case(name: &str) -> String {
+    let pascal: String = words(name)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    if pascal.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{pascal}")
+    } else {
+        pascal
+    }
+}
+
+/// Splits a wire name into lowercase words on separators and case changes.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = name.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        // Plural acronyms such as `IPs` or `VPCs` stay a single word, and so
+        // do versioned ones such as `IPv6`.
+        let acronym_suffix = match next {
+            Some('s') => !chars.get(i + 2).is_some_and(|n| n.is_ascii_lowercase()),
+            Some('v') => chars.get(i + 2).is_some_and(|n| n.is_ascii_digit()),
+            _ => false,
+        };
+        let boundary = c.is_ascii_uppercase()
+            && prev.is_some_and(|p| {
+                p.is_ascii_lowercase()
+                    || p.is_ascii_digit()
+                    || (p.is_ascii_uppercase()
+                        && next.is_some_and(|n| n.is_ascii_lowercase())
+                        && !acronym_suffix)
+            });
+
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// Renames component schemas whose type or module identifier collides with
+/// an earlier one, e.g. `Foo.Bar` after `foo_bar`, by adding the first free
+/// numeric suffix, and rewrites every ref to them.
+pub fn disambiguate_schemas(spec: &mut OpenAPI) -> Result<()> {
+    let Some(components) = &spec.components else {
+        return Ok(());
+    };
+
+    let mut types = HashSet::new();
+    let mut modules = HashSet::new();
+    let mut renames = HashMap::new();
+    for name in components.schemas.keys() {
+        let mut unique = name.clone();
+        let mut n = 2;
+        while types.contains(&type_ident(&unique)) || modules.contains(&module_ident(&unique)) {
+            unique = format!("{name}_{n}");
+            n += 1;
+        }
+        types.insert(type_ident(&unique));
+        modules.insert(module_ident(&unique));
+        if unique != *name {
+            renames.insert(name.clone(), unique);
+        }
+    }
+    if renames.is_empty() {
+        return Ok(());
+    }
+
+    tracing::warn!(?renames, "renamed schemas with colliding identifiers");
+    let refs: HashMap<String, String> = renames
+        .iter()
+        .map(|(from, to)| {
+            (
+                format!("{SCHEMA_REF_PREFIX}{}", escape_token(from)),
+                format!("{SCHEMA_REF_PREFIX}{}", escape_token(to)),
+            )
+        })
+        .collect();
+
+    let mut root = serde_json::to_value(&*spec).with_context(|| "failed to serialize spec")?;
+    rewrite_refs(&mut root, &refs);
+    if let Some(Value::Object(schemas)) = root.pointer_mut("/components/schemas") {
+        *schemas = std::mem::take(schemas)
+            .into_iter()
+            .map(|(name, schema)| (renames.get(&name).cloned().unwrap_or(name), schema))
+            .collect();
+    }
+    *spec = serde_json::from_value(root).with_context(|| "failed to rename schemas")?;
+
+    Ok(())
+}
+
+pub(crate) fn rewrite_refs(value: &mut Value, refs: &HashMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                if let Some(renamed) = refs.get(reference.as_str()) {
+                    *reference = renamed.clone();
+                }
+            }
+            map.values_mut().for_each(|v| rewrite_refs(v, refs));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rewrite_refs(v, refs)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn splits_words_on_separators_and_case() {
+        assert_eq!(
+            to_snake_case("droplet-actions.post"),
+            "droplet_actions_post"
+        );
+        assert_eq!(
+            to_snake_case("createServerRequest"),
+            "create_server_request"
+        );
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(to_snake_case("floatingIPs"), "floating_ips");
+        assert_eq!(to_snake_case("VPCsList"), "vpcs_list");
+        assert_eq!(to_snake_case("publicIPv6"), "public_ipv6");
+        assert_eq!(
+            to_pascal_case("scaleway.instance.v1.Server"),
+            "ScalewayInstanceV1Server"
+        );
+        assert_eq!(to_pascal_case("2fa"), "_2fa");
+    }
+
+    #[test]
+    fn escapes_keyword_fields() {
+        assert_eq!(field_ident("type"), ("r#type".into(), None));
+        assert_eq!(field_ident("self"), ("self_".into(), Some("self".into())));
+        assert_eq!(field_ident("2fa"), ("_2fa".into(), Some("2fa".into())));
+        assert_eq!(field_ident(""), ("_".into(), Some("".into())));
+        assert_eq!(field_ident("ipv4_address"), ("ipv4_address".into(), None));
+        assert_eq!(
+            field_ident("X-Request-Id"),
+            ("x_request_id".into(), Some("X-Request-Id".into()))
+        );
+    }
+
+    #[test]
+    fn suffixes_colliding_fields() {
+        let (ident, rename) = unique_field("foo-bar", |i| i == "foo_bar");
+        assert_eq!(
+            (ident.as_str(), rename.as_deref()),
+            ("foo_bar2", Some("foo-bar"))
+        );
+
+        // A suffixed keyword needs no raw prefix.
+        let (ident, _) = unique_field("type", |i| i == "r#type");
+        assert_eq!(ident, "type2");
+    }
+
+    #[test]
+    fn escapes_types_and_modules() {
+        assert_eq!(type_ident("Result"), "ResultValue");
+        assert_eq!(type_ident("option"), "OptionValue");
+        assert_eq!(type_ident("self"), "SelfValue");
+        assert_eq!(type_ident(""), "Empty");
+        assert_eq!(type_ident("ResultList"), "ResultList");
+        assert_eq!(variant_ident("result"), "Result");
+        assert_eq!(module_ident("Domain Records"), "domain_records");
+        assert_eq!(module_ident("Type"), "type_");
+        assert_eq!(module_ident("super"), "super_");
+        assert_eq!(module_ident("1-click"), "_1_click");
+    }
+
+    #[test]
+    fn renames_colliding_schemas() {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": { "title": "test", "version": "1" },
+            "paths": {},
+            "components": { "schemas": {
+                "Foo.Bar": { "type": "string" },
+                "foo_bar": { "$ref": "#/components/schemas/FooBar" },
+                "FooBar": { "items": { "$ref": "#/components/schemas/foo_bar" } },
+            } },
+        }))
+        .unwrap();
+        disambiguate_schemas(&mut spec).unwrap();
+
+        let root = serde_json::to_value(&spec).unwrap();
+        let schemas = &root["components"]["schemas"];
+        let names: Vec<&str> = schemas
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(names, ["Foo.Bar", "FooBar_2", "foo_bar_3"]);
+        assert_eq!(
+            schemas["FooBar_2"]["items"]["$ref"],
+            "#/components/schemas/foo_bar_3"
+        );
+        // `foo_bar_2` would be `FooBar2` again.
+        assert_eq!(
+            schemas["foo_bar_3"]["$ref"],
+            "#/components/schemas/FooBar_2"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::client::follow;
+use super::filter::OperationMatcher;
+use super::model::object_properties;
+use crate::resolver::resolve::SCHEMA_REF_PREFIX;
+use crate::Config;
+use anyhow::{Context, Result};
+use openapiv3::{Components, Operation, ReferenceOr, Schema, SchemaKind, Type};
+use serde::{Deserialize, Serialize};
+
+/// Properties of a list response that describe the page rather than hold
+/// its items.
+const PAGE_PROPERTIES: &[&str] = &["links", "meta", "result_info"];
+
+/// Where a list response says whether another page follows. Every variant
+/// but `None` is generated as the `Pagination` variant of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaginationStyle {
+    NextLink,
+    NextPage,
+    TotalCount,
+    ResultInfo,
+    /// The operation is not paginated, whatever its response looks like.
+    None,
+}
+
+impl PaginationStyle {
+    fn variant(self) -> Option<&'static str> {
+        match self {
+            Self::NextLink => Some("NextLink"),
+            Self::NextPage => Some("NextPage"),
+            Self::TotalCount => Some("TotalCount"),
+            Self::ResultInfo => Some("ResultInfo"),
+            Self::None => None,
+        }
+    }
+}
+
+/// Pagination configured for the operations matched by a `[pagination]`
+/// key, written like an `include_only` entry.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PaginationOverride {
+    pub style: PaginationStyle,
+    /// Response property holding the items, `result` for `result_info` and
+    /// otherwise the only array property if omitted.
+    pub items: Option<String>,
+}
+
+/// The `[pagination]` entries of the config, first match wins.
+#[derive(Debug, Default)]
+pub(crate) struct PaginationOverrides(Vec<(OperationMatcher, PaginationOverride)>);
+
+impl PaginationOverrides {
+    pub(crate) fn new(config: &Config) -> Result<Self> {
+        config
+            .pagination()
+            .map(|(entry, pagination)| {
+                let matcher = OperationMatcher::new(std::slice::from_ref(entry))
+                    .with_context(|| format!("invalid pagination entry: {entry}"))?;
+                Ok((matcher, pagination.clone()))
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    pub(crate) fn get(&self, path: &str, op: &Operation) -> Option<&PaginationOverride> {
+        self.0
+            .iter()
+            .find(|(matcher, _)| matcher.matches(path, op))
+            .map(|(_, pagination)| pagination)
+    }
+}
+
+/// A paginated list operation.
+#[derive(Debug)]
+pub(crate) struct Paging {
+    /// Variant of the generated `Pagination` enum.
+    pub variant: &'static str,
+    /// Response property holding the items of a page.
+    pub items: String,
+    pub item_schema: ReferenceOr<Box<Schema>>,
+}
+
+/// Recognizes the pagination style of a list operation from its `response`
+/// schema, unless `forced` by the config. Page numbered styles also need the
+/// operation to take a `page` query parameter, and a response with a single
+/// array property and no other hint is listed until an empty page.
+pub(crate) fn detect(
+    components: &Components,
+    response: &ReferenceOr<Schema>,
+    page_param: bool,
+    forced: Option<&PaginationOverride>,
+) -> Option<Paging> {
+    let response = follow(response, SCHEMA_REF_PREFIX, |n| components.schemas.get(n))?;
+    let properties = object_properties(components, response)?;
+    let property = |name: &str| {
+        properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, prop)| follow(prop, SCHEMA_REF_PREFIX, |n| components.schemas.get(n)))
+    };
+    let nested = |name: &str, child: &str| {
+        property(name)
+            .and_then(|schema| object_properties(components, schema))
+            .is_some_and(|props| props.iter().any(|(n, _)| n == child))
+    };
+
+    let style = match forced {
+        Some(forced) => forced.style,
+        None if nested("links", "pages") => PaginationStyle::NextLink,
+        None if !page_param => return None,
+        None if nested("meta", "pagination") => PaginationStyle::NextPage,
+        None if property("result_info").is_some() => PaginationStyle::ResultInfo,
+        None => PaginationStyle::TotalCount,
+    };
+    let variant = style.variant()?;
+
+    let mut arrays = properties.iter().filter_map(|(name, schema)| {
+        if PAGE_PROPERTIES.contains(&name.as_str()) {
+            return None;
+        }
+        let schema = follow(schema, SCHEMA_REF_PREFIX, |n| components.schemas.get(n))?;
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Array(array)) => Some((name, array.items.clone()?)),
+            _ => None,
+        }
+    });
+    let wanted = forced
+        .and_then(|f| f.items.as_deref())
+        .or((style == PaginationStyle::ResultInfo).then_some("result"));
+    let found = match wanted {
+        Some(wanted) => arrays.find(|(name, _)| *name == wanted),
+        None => arrays.next().filter(|_| arrays.next().is_none()),
+    };
+    let Some((items, item_schema)) = found else {
+        if forced.is_some() {
+            tracing::warn!(?wanted, "no items property in paginated response");
+        }
+        return None;
+    };
+
+    Some(Paging {
+        variant,
+        items: items.clone(),
+        item_schema,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn components() -> Components {
+        serde_json::from_value(json!({ "schemas": {
+            "Droplet": { "type": "object", "properties": { "id": { "type": "integer" } } },
+            "Droplets": { "type": "array", "items": { "$ref": "#/components/schemas/Droplet" } },
+            "Links": {
+                "type": "object",
+                "properties": { "pages": { "type": "object" } },
+            },
+        } }))
+        .unwrap()
+    }
+
+    fn object(properties: Value) -> ReferenceOr<Schema> {
+        serde_json::from_value(json!({ "type": "object", "properties": properties })).unwrap()
+    }
+
+    fn droplets() -> Value {
+        json!({ "$ref": "#/components/schemas/Droplets" })
+    }
+
+    fn detected(response: Value, page_param: bool) -> Option<(&'static str, String)> {
+        let paging = detect(&components(), &object(response), page_param, None)?;
+        Some((paging.variant, paging.items))
+    }
+
+    #[test]
+    fn detects_next_links() {
+        let response = json!({
+            "droplets": droplets(),
+            "links": { "$ref": "#/components/schemas/Links" },
+        });
+        assert_eq!(
+            detected(response, false),
+            Some(("NextLink", "droplets".into()))
+        );
+    }
+
+    #[test]
+    fn page_numbers_need_a_page_parameter() {
+        let response = json!({
+            "servers": droplets(),
+            "meta": {
+                "type": "object",
+                "properties": { "pagination": { "type": "object" } },
+            },
+        });
+        assert_eq!(detected(response.clone(), false), None);
+        assert_eq!(
+            detected(response, true),
+            Some(("NextPage", "servers".into()))
+        );
+    }
+
+    #[test]
+    fn result_info_lists_result() {
+        let response = json!({
+            "errors": { "type": "array", "items": { "type": "string" } },
+            "result": droplets(),
+            "result_info": { "type": "object" },
+        });
+        assert_eq!(
+            detected(response, true),
+            Some(("ResultInfo", "result".into()))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_total_count_with_a_single_array() {
+        let single = json!({ "servers": droplets(), "total_count": { "type": "integer" } });
+        assert_eq!(
+            detected(single, true),
+            Some(("TotalCount", "servers".into()))
+        );
+
+        let ambiguous = json!({ "servers": droplets(), "images": droplets() });
+        assert_eq!(detected(ambiguous, true), None);
+        assert_eq!(detected(json!({ "id": { "type": "string" } }), true), None);
+    }
+
+    #[test]
+    fn follows_forced_styles() {
+        let components = components();
+        let response = object(json!({ "servers": droplets(), "images": droplets() }));
+        let forced = |style, items: Option<&str>| PaginationOverride {
+            style,
+            items: items.map(str::to_string),
+        };
+
+        let paging = detect(
+            &components,
+            &response,
+            false,
+            Some(&forced(PaginationStyle::NextPage, Some("images"))),
+        )
+        .unwrap();
+        assert_eq!(
+            (paging.variant, paging.items.as_str()),
+            ("NextPage", "images")
+        );
+        assert_eq!(
+            paging.item_schema,
+            ReferenceOr::ref_("#/components/schemas/Droplet")
+        );
+
+        let none = forced(PaginationStyle::None, None);
+        assert!(detect(&components, &response, true, Some(&none)).is_none());
+        let missing = forced(PaginationStyle::NextPage, Some("volumes"));
+        assert!(detect(&components, &response, true, Some(&missing)).is_none());
+    }
+}
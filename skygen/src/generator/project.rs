@@ -51,13 +51,12 @@ async fn render_templates(
     Ok(())
 }
 
-pub async fn bootstrap_lib(config: &Config, out_dir: impl AsRef<Path>) -> Result<()> {
-    create_dirs(out_dir.as_ref())
-        .await
-        .with_context(|| "failed to create project directories")?;
-
+/// Loads every embedded Tera template so the project, model and client
+/// generators all render from the same registry.
+pub fn load_templates() -> Result<Tera> {
     let mut tera = Tera::default();
     for name in [
+        "templates/auth.rs.tera",
         "templates/cargo.toml.tera",
         "templates/operation.rs.tera",
         "templates/model.rs.tera",
@@ -75,8 +74,23 @@ pub async fn bootstrap_lib(config: &Config, out_dir: impl AsRef<Path>) -> Result
         )?;
     }
 
+    Ok(tera)
+}
+
+pub async fn bootstrap_lib(
+    config: &Config,
+    shared_modules: &[String],
+    out_dir: impl AsRef<Path>,
+) -> Result<()> {
+    create_dirs(out_dir.as_ref())
+        .await
+        .with_context(|| "failed to create project directories")?;
+
+    let tera = load_templates()?;
+
     let mut base_ctx = TeraContext::new();
     base_ctx.insert("config", config);
+    base_ctx.insert("shared_modules", shared_modules);
 
     let plans = [
         RenderPlan {
@@ -108,11 +122,46 @@ pub async fn bootstrap_lib(config: &Config, out_dir: impl AsRef<Path>) -> Result
             out_rel: "src/client.rs",
             extra: noop,
         },
+        RenderPlan {
+            template: "lib/credentials.rs",
+            out_rel: "src/credentials.rs",
+            extra: noop,
+        },
         RenderPlan {
             template: "lib/errors.rs",
             out_rel: "src/errors.rs",
             extra: noop,
         },
+        RenderPlan {
+            template: "lib/nullable.rs",
+            out_rel: "src/nullable.rs",
+            extra: noop,
+        },
+        RenderPlan {
+            template: "lib/pagination.rs",
+            out_rel: "src/pagination.rs",
+            extra: noop,
+        },
+        RenderPlan {
+            template: "lib/rate_limit.rs",
+            out_rel: "src/rate_limit.rs",
+            extra: noop,
+        },
+        RenderPlan {
+            template: "lib/retry.rs",
+            out_rel: "src/retry.rs",
+            extra: noop,
+        },
+        RenderPlan {
+            template: "lib/types.rs",
+            out_rel: "src/types.rs",
+            extra: noop,
+        },
+        RenderPlan {
+            template: "lib/validation.rs",
+            out_rel: "src/validation.rs",
+            extra: noop,
+        },
     ];
 
     write_rs_files(out_dir.as_ref(), &rs_files).await?;
@@ -0,0 +1,388 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::filter::collect_refs;
+use super::naming::{rewrite_refs, type_ident};
+use crate::resolver::pointer::{escape_token, parse_json_pointer_ref};
+use crate::resolver::resolve::SCHEMA_REF_PREFIX;
+use anyhow::{Context, Result};
+use openapiv3::OpenAPI;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// How `readOnly` and `writeOnly` properties are generated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadWriteMode {
+    /// One type per schema; `readOnly` fields are never serialized and
+    /// `writeOnly` fields never deserialized.
+    #[default]
+    Skip,
+    /// Request bodies use a `FooCreate` copy of `Foo` without its `readOnly`
+    /// properties, responses use `Foo` without its `writeOnly` ones.
+    Split,
+}
+
+/// Gives every schema that a request body reaches and whose shape differs
+/// between requests and responses a `<name>_create` copy without its
+/// `readOnly` properties, and points request bodies at those copies. Schemas
+/// only used by requests lose their `readOnly` properties in place, the
+/// remaining ones lose their `writeOnly` properties.
+pub fn split_schemas(spec: &mut OpenAPI) -> Result<()> {
+    let mut root = serde_json::to_value(&*spec).with_context(|| "failed to serialize spec")?;
+    let Some(Value::Object(schemas)) = root.pointer("/components/schemas").cloned() else {
+        return Ok(());
+    };
+
+    let refs: HashMap<&str, HashSet<String>> = schemas
+        .iter()
+        .map(|(name, schema)| (name.as_str(), schema_refs(schema)))
+        .collect();
+
+    // Schemas with access modifiers of their own, or that refer to one.
+    let mut differing: HashSet<String> = schemas
+        .iter()
+        .filter(|(_, schema)| has_access(schema))
+        .map(|(name, _)| name.clone())
+        .collect();
+    loop {
+        let before = differing.len();
+        for (name, targets) in &refs {
+            if targets.iter().any(|t| differing.contains(t)) {
+                differing.insert(name.to_string());
+            }
+        }
+        if differing.len() == before {
+            break;
+        }
+    }
+
+    let mut request_roots = Vec::new();
+    let mut others = root.clone();
+    for item in others["paths"]
+        .as_object_mut()
+        .into_iter()
+        .flat_map(|p| p.values_mut())
+    {
+        for method in METHODS {
+            if let Some(body) = item
+                .get_mut(*method)
+                .and_then(Value::as_object_mut)
+                .and_then(|op| op.remove("requestBody"))
+            {
+                collect_refs(&body, &mut request_roots);
+            }
+        }
+    }
+    if let Some(components) = others["components"].as_object_mut() {
+        if let Some(bodies) = components.remove("requestBodies") {
+            collect_refs(&bodies, &mut request_roots);
+        }
+        components.remove("schemas");
+    }
+    let mut other_roots = Vec::new();
+    collect_refs(&others, &mut other_roots);
+
+    let requested = reachable(&request_roots, &refs);
+    let elsewhere = reachable(&other_roots, &refs);
+
+    let mut taken: HashSet<String> = schemas.keys().map(|n| type_ident(n)).collect();
+    let mut copies = HashMap::new();
+    for name in schemas.keys() {
+        if differing.contains(name) && requested.contains(name) && elsewhere.contains(name) {
+            let mut copy = format!("{name}_create");
+            let mut n = 2;
+            while taken.contains(&type_ident(&copy)) {
+                copy = format!("{name}_create_{n}");
+                n += 1;
+            }
+            taken.insert(type_ident(&copy));
+            copies.insert(name.clone(), copy);
+        }
+    }
+    let renames: HashMap<String, String> = copies
+        .iter()
+        .map(|(from, to)| (schema_ref(from), schema_ref(to)))
+        .collect();
+
+    let mut split = Map::new();
+    for (name, schema) in schemas {
+        if !differing.contains(&name) {
+            split.insert(name, schema);
+            continue;
+        }
+
+        let mut request = schema.clone();
+        remove_properties(&mut request, "readOnly");
+        clear_access(&mut request);
+        rewrite_refs(&mut request, &renames);
+        match copies.get(&name) {
+            Some(copy) => {
+                let mut response = schema;
+                remove_properties(&mut response, "writeOnly");
+                clear_access(&mut response);
+                split.insert(name, response);
+                split.insert(copy.clone(), request);
+            }
+            None if requested.contains(&name) => {
+                split.insert(name, request);
+            }
+            None => {
+                let mut response = schema;
+                remove_properties(&mut response, "writeOnly");
+                clear_access(&mut response);
+                split.insert(name, response);
+            }
+        }
+    }
+    tracing::debug!(copies = copies.len(), "split request and response schemas");
+
+    root["components"]["schemas"] = Value::Object(split);
+    for item in root["paths"]
+        .as_object_mut()
+        .into_iter()
+        .flat_map(|p| p.values_mut())
+    {
+        for method in METHODS {
+            if let Some(body) = item
+                .get_mut(*method)
+                .and_then(|op| op.get_mut("requestBody"))
+            {
+                rewrite_refs(body, &renames);
+            }
+        }
+    }
+    if let Some(bodies) = root.pointer_mut("/components/requestBodies") {
+        rewrite_refs(bodies, &renames);
+    }
+
+    *spec = serde_json::from_value(root).with_context(|| "failed to split schemas")?;
+    Ok(())
+}
+
+fn schema_ref(name: &str) -> String {
+    format!("{SCHEMA_REF_PREFIX}{}", escape_token(name))
+}
+
+/// Names of the component schemas `value` refers to.
+fn schema_refs(value: &Value) -> HashSet<String> {
+    let mut refs = Vec::new();
+    collect_refs(value, &mut refs);
+    refs.iter().filter_map(|r| schema_name(r)).collect()
+}
+
+fn schema_name(reference: &str) -> Option<String> {
+    match parse_json_pointer_ref(reference).ok()?.as_slice() {
+        [components, schemas, name] if components == "components" && schemas == "schemas" => {
+            Some(name.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Schemas reachable from `roots`, following refs between schemas.
+fn reachable(roots: &[String], refs: &HashMap<&str, HashSet<String>>) -> HashSet<String> {
+    let mut pending: Vec<String> = roots.iter().filter_map(|r| schema_name(r)).collect();
+    let mut seen = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if seen.insert(name.clone()) {
+            pending.extend(refs.get(name.as_str()).into_iter().flatten().cloned());
+        }
+    }
+    seen
+}
+
+/// Calls `f` with every schema nested in `schema` without following refs.
+fn for_each_subschema(schema: &mut Value, f: &mut impl FnMut(&mut Value)) {
+    let Value::Object(map) = schema else {
+        return;
+    };
+    if let Some(Value::Object(props)) = map.get_mut("properties") {
+        for prop in props.values_mut() {
+            f(prop);
+        }
+    }
+    for key in ["items", "additionalProperties", "not"] {
+        if let Some(child) = map.get_mut(key) {
+            f(child);
+        }
+    }
+    for key in ["allOf", "oneOf", "anyOf"] {
+        if let Some(Value::Array(members)) = map.get_mut(key) {
+            members.iter_mut().for_each(&mut *f);
+        }
+    }
+}
+
+fn is_marked(schema: &Value, access: &str) -> bool {
+    schema.get(access).and_then(Value::as_bool) == Some(true)
+}
+
+/// Whether any property nested in `schema` is `readOnly` or `writeOnly`.
+fn has_access(schema: &Value) -> bool {
+    let props = schema.get("properties").and_then(Value::as_object);
+    let marked = props.is_some_and(|props| {
+        props
+            .values()
+            .any(|p| is_marked(p, "readOnly") || is_marked(p, "writeOnly"))
+    });
+    if marked {
+        return true;
+    }
+
+    let children = ["items", "additionalProperties", "not"]
+        .into_iter()
+        .filter_map(|key| schema.get(key));
+    let members = ["allOf", "oneOf", "anyOf"]
+        .into_iter()
+        .filter_map(|key| schema.get(key)?.as_array())
+        .flatten();
+    props
+        .into_iter()
+        .flat_map(|props| props.values())
+        .chain(children)
+        .chain(members)
+        .any(has_access)
+}
+
+/// Removes the properties marked with `access` from `schema` and every
+/// schema nested in it.
+fn remove_properties(schema: &mut Value, access: &str) {
+    if let Some(Value::Object(props)) = schema.get_mut("properties") {
+        let removed: Vec<String> = props
+            .iter()
+            .filter(|(_, p)| is_marked(p, access))
+            .map(|(name, _)| name.clone())
+            .collect();
+        props.retain(|name, _| !removed.contains(name));
+        if let Some(Value::Array(required)) = schema.get_mut("required") {
+            required.retain(|r| !r.as_str().is_some_and(|r| removed.iter().any(|n| n == r)));
+        }
+    }
+    for_each_subschema(schema, &mut |child| remove_properties(child, access));
+}
+
+/// Drops the `readOnly`/`writeOnly` flags of `schema` and every schema
+/// nested in it once the shapes reflect them.
+fn clear_access(schema: &mut Value) {
+    if let Value::Object(map) = schema {
+        map.retain(|key, v| !(matches!(key.as_str(), "readOnly" | "writeOnly") && v.is_boolean()));
+    }
+    for_each_subschema(schema, &mut clear_access);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user() -> Value {
+        json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": { "type": "string", "readOnly": true },
+                "name": { "type": "string" },
+                "password": { "type": "string", "writeOnly": true },
+            },
+        })
+    }
+
+    fn body(schema: &str) -> Value {
+        json!({ "description": schema, "content": { "application/json": {
+            "schema": { "$ref": format!("#/components/schemas/{schema}") },
+            "example": { "readOnly": true },
+        } } })
+    }
+
+    fn split() -> Value {
+        let mut spec: OpenAPI = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": { "title": "test", "version": "1" },
+            "paths": {
+                "/users": {
+                    "post": {
+                        "requestBody": body("User"),
+                        "responses": { "201": body("User") },
+                    },
+                },
+                "/invites": {
+                    "post": {
+                        "requestBody": body("Invite"),
+                        "responses": { "200": body("Audit") },
+                    },
+                },
+            },
+            "components": { "schemas": { "Audit": user(), "Invite": user(), "User": user() } },
+        }))
+        .unwrap();
+        split_schemas(&mut spec).unwrap();
+        serde_json::to_value(&spec).unwrap()
+    }
+
+    fn properties(spec: &Value, schema: &str) -> Vec<String> {
+        spec["components"]["schemas"][schema]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn copies_schemas_shared_by_requests_and_responses() {
+        let spec = split();
+        assert_eq!(properties(&spec, "User"), ["id", "name"]);
+        assert_eq!(properties(&spec, "User_create"), ["name", "password"]);
+        assert_eq!(
+            spec["components"]["schemas"]["User_create"]["required"],
+            json!(["name"])
+        );
+
+        let post = &spec["paths"]["/users"]["post"];
+        let schema = |body: &Value| body["content"]["application/json"]["schema"]["$ref"].clone();
+        assert_eq!(
+            schema(&post["requestBody"]),
+            json!("#/components/schemas/User_create")
+        );
+        assert_eq!(
+            schema(&post["responses"]["201"]),
+            json!("#/components/schemas/User")
+        );
+    }
+
+    #[test]
+    fn strips_request_and_response_only_schemas_in_place() {
+        let spec = split();
+        assert_eq!(properties(&spec, "Invite"), ["name", "password"]);
+        assert_eq!(properties(&spec, "Audit"), ["id", "name"]);
+        assert!(spec["components"]["schemas"].get("Invite_create").is_none());
+    }
+
+    #[test]
+    fn clears_access_flags_of_schemas_only() {
+        let spec = split();
+        let id = &spec["components"]["schemas"]["User"]["properties"]["id"];
+        assert!(id.get("readOnly").is_none());
+
+        let example = &spec["paths"]["/users"]["post"]["requestBody"]["content"]
+            ["application/json"]["example"];
+        assert_eq!(example, &json!({ "readOnly": true }));
+    }
+}
@@ -15,9 +15,12 @@
 pub mod generator;
 pub mod resolver;
 
+pub use generator::{generate, generate_blocking, load_spec};
+
 use core::fmt;
 use include_dir::{include_dir, Dir};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -40,6 +43,8 @@ pub struct Cli {
 #[derive(StructOpt)]
 pub enum Commands {
     Generate(GenerateArgs),
+    /// Write a spec split across files as a single document
+    Bundle(BundleArgs),
 }
 
 #[derive(StructOpt)]
@@ -55,6 +60,35 @@ pub struct GenerateArgs {
     /// Skygen config for generating the SDK.
     #[structopt(short = "c", long = "config")]
     pub config: PathBuf,
+
+    /// Maximum nesting depth while resolving $refs, overrides `max_depth`
+    /// from the config.
+    #[structopt(long = "max-depth")]
+    pub max_depth: Option<usize>,
+}
+
+#[derive(StructOpt)]
+pub struct BundleArgs {
+    /// OpenAPIv3 Spec file to bundle, refs into other files are followed
+    #[structopt(short = "s", long = "schema")]
+    pub schema: PathBuf,
+
+    /// File to write the bundled spec to, stdout if omitted
+    #[structopt(short = "o", long = "output")]
+    pub output: Option<PathBuf>,
+
+    /// Inline every $ref instead of keeping refs into `#/components`
+    #[structopt(long = "dereference")]
+    pub dereference: bool,
+
+    /// Output format (yaml or json), defaults to the output file extension
+    /// and YAML otherwise
+    #[structopt(short = "f", long = "format")]
+    pub format: Option<resolver::bundle::OutputFormat>,
+
+    /// Maximum nesting depth while resolving $refs, 128 if omitted
+    #[structopt(long = "max-depth")]
+    pub max_depth: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -69,16 +103,76 @@ pub struct Config {
     authors: Vec<String>,
     include_only: Option<Vec<String>>,
     exclude: Option<Vec<String>>,
+    max_depth: Option<usize>,
+    map_type: Option<generator::model::MapType>,
+    read_write: Option<generator::read_write::ReadWriteMode>,
+    credentials: Option<BTreeMap<String, generator::auth::CredentialSource>>,
+    retry_safe: Option<Vec<String>>,
+    pagination: Option<BTreeMap<String, generator::pagination::PaginationOverride>>,
+}
+
+impl Config {
+    /// Maximum nesting depth the resolver accepts before giving up.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+            .unwrap_or(resolver::resolve::DEFAULT_MAX_DEPTH)
+    }
+
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = Some(depth);
+    }
+
+    /// Map container generated for `additionalProperties`.
+    pub fn map_type(&self) -> generator::model::MapType {
+        self.map_type.unwrap_or_default()
+    }
+
+    /// How `readOnly` and `writeOnly` properties are generated.
+    pub fn read_write(&self) -> generator::read_write::ReadWriteMode {
+        self.read_write.unwrap_or_default()
+    }
+
+    /// Operations retried on failure even though their method is not
+    /// idempotent, written like `include_only` entries.
+    pub fn retry_safe(&self) -> &[String] {
+        self.retry_safe.as_deref().unwrap_or_default()
+    }
+
+    /// Pagination of the operations matched by each key, overriding the
+    /// style detected from their responses.
+    pub fn pagination(
+        &self,
+    ) -> impl Iterator<Item = (&String, &generator::pagination::PaginationOverride)> {
+        self.pagination.iter().flatten()
+    }
+
+    /// Where the generated credential providers look, keyed by security
+    /// scheme name.
+    pub fn credentials(
+        &self,
+    ) -> impl Iterator<Item = (&String, &generator::auth::CredentialSource)> {
+        self.credentials.iter().flatten()
+    }
 }
 
 #[derive(Debug)]
 pub enum ResolverError {
     InvalidRef(String),
     PointerEscape(String),
-    MissingTarget { ref_: String, path: String },
-    TypeMismatch { ref_: String, expected: String },
+    MissingTarget {
+        ref_: String,
+        path: String,
+    },
+    TypeMismatch {
+        ref_: String,
+        expected: String,
+    },
     CycleDetected(String),
-    MaxDeptExceeded { ref_: String, depth: usize },
+    MaxDeptExceeded {
+        ref_: String,
+        depth: usize,
+        chain: Vec<String>,
+    },
 }
 
 impl fmt::Display for ResolverError {
@@ -93,8 +187,12 @@ impl fmt::Display for ResolverError {
                 write!(f, "resolved $ref {ref_} is not a {expected}")
             }
             Self::CycleDetected(r) => write!(f, "cycle detected while resolving $ref: {r}"),
-            Self::MaxDeptExceeded { ref_, depth } => {
-                write!(f, "max resolution depth {ref_} exceeded at {depth}")
+            Self::MaxDeptExceeded { ref_, depth, chain } => {
+                write!(f, "max resolution depth {depth} exceeded at {ref_}")?;
+                if !chain.is_empty() {
+                    write!(f, " (ref chain: {})", chain.join(" -> "))?;
+                }
+                Ok(())
             }
         }
     }
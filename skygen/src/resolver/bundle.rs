@@ -0,0 +1,88 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::resolve::Resolver;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+
+/// How refs are treated when a spec is bundled into a single document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleMode {
+    /// Refs into `#/components` stay in place; targets in other files, whole
+    /// files included, are moved to the matching root `components` section
+    /// and referenced from there.
+    Bundled,
+    /// Every ref is inlined. Recursive schemas are the only exception since
+    /// they cannot be expanded, they are kept under `#/components/schemas`.
+    Dereferenced,
+}
+
+/// Serialization format of a bundled spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Yaml,
+    Json,
+}
+
+impl OutputFormat {
+    /// Picks the format from the extension of `path`, if it has a known one.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "json" => Ok(Self::Json),
+            _ => bail!("unsupported output format: {s}"),
+        }
+    }
+}
+
+/// Resolves the spec at `path`, which may be split across files, into a
+/// single document.
+pub fn bundle(path: impl AsRef<Path>, mode: BundleMode, max_depth: usize) -> Result<Value> {
+    let mut resolver = Resolver::from_path(path)?.max_depth(max_depth);
+    if mode == BundleMode::Bundled {
+        resolver = resolver.preserve("#/components/");
+    }
+
+    resolver
+        .resolve()
+        .with_context(|| "failed to resolve spec references")
+}
+
+/// Serializes a bundled spec. Object keys are written in sorted order so
+/// bundles of the same spec diff cleanly.
+pub fn render(doc: &Value, format: OutputFormat) -> Result<String> {
+    let data = match format {
+        OutputFormat::Yaml => serde_yaml::to_string(doc).with_context(|| "failed to write YAML")?,
+        OutputFormat::Json => {
+            let mut data =
+                serde_json::to_string_pretty(doc).with_context(|| "failed to write JSON")?;
+            data.push('\n');
+            data
+        }
+    };
+
+    Ok(data)
+}
@@ -0,0 +1,162 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Context, Result};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Loads the documents a spec is split across, parsing each file only once.
+#[derive(Debug, Default)]
+pub struct DocumentLoader {
+    cache: RefCell<HashMap<PathBuf, Rc<Value>>>,
+}
+
+impl DocumentLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the canonical path and parsed contents of the document at `path`.
+    pub fn load(&self, path: &Path) -> Result<(PathBuf, Rc<Value>)> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("failed to locate document {}", path.display()))?;
+
+        if let Some(doc) = self.cache.borrow().get(&path) {
+            return Ok((path, Rc::clone(doc)));
+        }
+
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read document {}", path.display()))?;
+        let doc = Rc::new(parse_document(&path, &data)?);
+        self.cache
+            .borrow_mut()
+            .insert(path.clone(), Rc::clone(&doc));
+
+        Ok((path, doc))
+    }
+}
+
+/// Parses a YAML or JSON document, picking the format from the file extension.
+pub fn parse_document(path: &Path, data: &str) -> Result<Value> {
+    let extension = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .with_context(|| format!("failed to parse file extension of {}", path.display()))?;
+
+    let doc = match extension.to_lowercase().as_str() {
+        "yaml" | "yml" => {
+            serde_yaml::from_str::<LenientValue>(data)
+                .with_context(|| format!("failed to parse YAML in {}", path.display()))?
+                .0
+        }
+        "json" => serde_json::from_str(data)
+            .with_context(|| format!("failed to parse json in {}", path.display()))?,
+        _ => bail!("unsupported file extension: {extension}"),
+    };
+
+    Ok(doc)
+}
+
+/// A JSON value that reads integer literals overflowing 64 bits as floats
+/// instead of rejecting the document. DigitalOcean, for one, documents
+/// `maximum: 18446744073709552000` on some database settings.
+struct LenientValue(Value);
+
+impl<'de> Deserialize<'de> for LenientValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(LenientVisitor)
+            .map(LenientValue)
+    }
+}
+
+struct LenientVisitor;
+
+impl<'de> Visitor<'de> for LenientVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON compatible value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Value, E> {
+        Ok(i64::try_from(v).map_or_else(|_| float(v as f64), Value::from))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Value, E> {
+        Ok(u64::try_from(v).map_or_else(|_| float(v as f64), Value::from))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(float(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(LenientValue(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut values = Map::new();
+        while let Some((key, LenientValue(value))) = map.next_entry::<String, _>()? {
+            values.insert(key, value);
+        }
+        Ok(Value::Object(values))
+    }
+}
+
+fn float(v: f64) -> Value {
+    serde_json::Number::from_f64(v).map_or(Value::Null, Value::Number)
+}
@@ -12,5 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod bundle;
+pub mod loader;
 pub mod pointer;
 pub mod resolve;
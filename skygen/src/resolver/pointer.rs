@@ -15,10 +15,148 @@
 use crate::ResolverError;
 use anyhow::Result;
 
+/// Parses a local `$ref` such as `#/components/schemas/Droplet` into its
+/// unescaped reference tokens.
+///
+/// The fragment is percent-decoded first since it is part of a URI, then split
+/// into RFC 6901 tokens with `~1` and `~0` unescaped to `/` and `~`.
 pub fn parse_json_pointer_ref(ref_str: &str) -> Result<Vec<String>, ResolverError> {
-    if ref_str.starts_with('#') {
-        return Err(ResolverError::InvalidRef(ref_str.to_string()));
+    let fragment = ref_str
+        .strip_prefix('#')
+        .ok_or_else(|| ResolverError::InvalidRef(ref_str.to_string()))?;
+
+    let pointer = percent_decode(fragment, ref_str)?;
+    parse_json_pointer(&pointer).map_err(|e| match e {
+        ResolverError::InvalidRef(_) => ResolverError::InvalidRef(ref_str.to_string()),
+        e => e,
+    })
+}
+
+/// Splits an RFC 6901 JSON pointer into unescaped reference tokens. The empty
+/// pointer refers to the whole document.
+pub fn parse_json_pointer(pointer: &str) -> Result<Vec<String>, ResolverError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tokens = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| ResolverError::InvalidRef(pointer.to_string()))?;
+
+    tokens.split('/').map(unescape_token).collect()
+}
+
+/// Escapes a single reference token so it can be appended to a pointer.
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_token(token: &str) -> Result<String, ResolverError> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => out.push('~'),
+            Some('1') => out.push('/'),
+            _ => return Err(ResolverError::PointerEscape(token.to_string())),
+        }
+    }
+
+    Ok(out)
+}
+
+fn percent_decode(fragment: &str, ref_str: &str) -> Result<String, ResolverError> {
+    let bytes = fragment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| ResolverError::PointerEscape(fragment.to_string()))?;
+        out.push(byte);
+        i += 3;
+    }
+
+    String::from_utf8(out).map_err(|_| ResolverError::InvalidRef(ref_str.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_component_refs() {
+        let tokens = parse_json_pointer_ref("#/components/schemas/Droplet").unwrap();
+        assert_eq!(tokens, ["components", "schemas", "Droplet"]);
+    }
+
+    #[test]
+    fn empty_fragment_is_the_whole_document() {
+        assert!(parse_json_pointer_ref("#").unwrap().is_empty());
+        assert!(parse_json_pointer("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn unescapes_tokens() {
+        let tokens = parse_json_pointer_ref("#/paths/~1v2~1droplets/a~0b~01").unwrap();
+        assert_eq!(tokens, ["paths", "/v2/droplets", "a~b~1"]);
+    }
+
+    #[test]
+    fn percent_decodes_before_unescaping() {
+        let tokens = parse_json_pointer_ref("#/paths/%7B id%7D/x%7E1y").unwrap();
+        assert_eq!(tokens, ["paths", "{ id}", "x/y"]);
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        for token in ["/v2/droplets/{id}", "a~b", "~1", "plain"] {
+            let pointer = format!("/{}", escape_token(token));
+            assert_eq!(parse_json_pointer(&pointer).unwrap(), [token]);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_refs() {
+        assert!(matches!(
+            parse_json_pointer_ref("components/schemas/Droplet"),
+            Err(ResolverError::InvalidRef(_))
+        ));
+        assert!(matches!(
+            parse_json_pointer_ref("#components"),
+            Err(ResolverError::InvalidRef(r)) if r == "#components"
+        ));
+        assert!(matches!(
+            parse_json_pointer_ref("#/a~2b"),
+            Err(ResolverError::PointerEscape(_))
+        ));
+        assert!(matches!(
+            parse_json_pointer_ref("#/a~"),
+            Err(ResolverError::PointerEscape(_))
+        ));
+        assert!(matches!(
+            parse_json_pointer_ref("#/a%2"),
+            Err(ResolverError::PointerEscape(_))
+        ));
+        assert!(matches!(
+            parse_json_pointer_ref("#/a%ff"),
+            Err(ResolverError::InvalidRef(_))
+        ));
     }
-    println!("{ref_str}");
-    Ok(Vec::new())
 }
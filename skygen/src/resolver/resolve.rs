@@ -12,76 +12,532 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::loader::DocumentLoader;
 use crate::ResolverError;
 use anyhow::Result;
-use serde_json::Value;
-use std::collections::HashSet;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub(crate) const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+const COMPONENTS_REF_PREFIX: &str = "#/components/";
+
+/// The sections of `components` in OpenAPI 3.0.
+const COMPONENT_SECTIONS: [&str; 9] = [
+    "schemas",
+    "responses",
+    "parameters",
+    "examples",
+    "requestBodies",
+    "headers",
+    "securitySchemes",
+    "links",
+    "callbacks",
+];
+
+/// Nesting depth allowed while resolving unless configured otherwise.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// The document a value was read from, used to resolve the refs it contains.
+#[derive(Debug, Clone)]
+pub struct Document {
+    path: Option<PathBuf>,
+    value: Rc<Value>,
+    is_root: bool,
+}
+
+/// Targets that are kept as named components instead of being inlined.
+#[derive(Debug, Default)]
+struct Hoisted {
+    /// Component section and name assigned to a ref target, keyed like
+    /// `WalkState::visited`.
+    names: HashMap<String, (&'static str, String)>,
+    /// Resolved components to add to the root `components`, by section.
+    components: BTreeMap<&'static str, BTreeMap<String, Value>>,
+}
+
+/// Bookkeeping for a single resolution pass.
+#[derive(Debug, Default)]
+pub struct WalkState {
+    /// Ref targets currently being expanded, keyed across documents.
+    visited: HashSet<String>,
+    /// The refs currently being expanded, outermost first.
+    chain: Vec<String>,
+    /// Nesting depth of the value being walked.
+    depth: usize,
+    /// Keys and indices leading to the value being walked, through the refs
+    /// followed on the way. Tells what a ref stands for, e.g. a parameter.
+    path: Vec<String>,
+}
 
 #[derive(Debug)]
 pub struct Resolver {
-    root: Value,
+    root: Document,
+    loader: DocumentLoader,
+    preserve: Vec<String>,
+    hoisted: RefCell<Hoisted>,
+    max_depth: usize,
 }
 
 impl Resolver {
+    /// Resolves an in-memory document. Relative file refs are looked up from
+    /// the current directory.
     pub fn new(root: Value) -> Self {
-        Self { root }
+        Self {
+            root: Document {
+                path: None,
+                value: Rc::new(root),
+                is_root: true,
+            },
+            loader: DocumentLoader::new(),
+            preserve: Vec::new(),
+            hoisted: RefCell::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Resolves the document at `path`, looking up relative file refs from the
+    /// directory of the file that contains them.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let loader = DocumentLoader::new();
+        let (path, value) = loader.load(path.as_ref())?;
+
+        Ok(Self {
+            root: Document {
+                path: Some(path),
+                value,
+                is_root: true,
+            },
+            loader,
+            preserve: Vec::new(),
+            hoisted: RefCell::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        })
     }
 
+    /// Leaves every `$ref` into the root document whose fragment starts with
+    /// `prefix` in place as a local ref instead of inlining its target, e.g.
+    /// `#/components/schemas/` to keep named models.
+    pub fn preserve(mut self, prefix: impl Into<String>) -> Self {
+        self.preserve.push(prefix.into());
+        self
+    }
+
+    /// Fails resolution once values nest deeper than `depth`, counting every
+    /// object and array level of the resolved output.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Inlines every ref that is not preserved.
+    ///
+    /// Schemas that refer back to themselves cannot be inlined, so they are
+    /// added to the root `components.schemas` and referenced from there. When
+    /// refs into a components section are preserved, targets in other
+    /// documents that belong in it, whole files included, are moved there as
+    /// well, keeping the root document self-contained.
     pub fn resolve(&self) -> Result<Value> {
-        let mut visited = HashSet::new();
-        self.walk(&self.root, &mut visited)
+        let mut state = WalkState::default();
+        let mut resolved = self.walk(&self.root.value, &self.root, &mut state)?;
+        self.merge_hoisted(&mut resolved)?;
+
+        Ok(resolved)
     }
 
-    pub fn walk(&self, current: &Value, visited: &mut HashSet<String>) -> Result<Value> {
-        match current {
+    fn is_preserved(&self, fragment: &str) -> bool {
+        self.preserve
+            .iter()
+            .any(|p| fragment.starts_with(p.as_str()))
+    }
+
+    /// The root components section an external ref target is moved to: the
+    /// one it sits in or, for whole files and other pointers, the one its
+    /// ref at `site` stands for. `None` unless refs into it are preserved.
+    fn hoisted_section(&self, fragment: &str, site: &[String]) -> Option<&'static str> {
+        let section = fragment
+            .strip_prefix(COMPONENTS_REF_PREFIX)
+            .and_then(|rest| component_section(rest.split('/').next()?))
+            .or_else(|| site_section(site))?;
+        self.is_preserved(&format!("{COMPONENTS_REF_PREFIX}{section}/"))
+            .then_some(section)
+    }
+
+    fn merge_hoisted(&self, resolved: &mut Value) -> Result<()> {
+        let hoisted = std::mem::take(&mut self.hoisted.borrow_mut().components);
+
+        for (section, hoisted) in hoisted {
+            let components = resolved
+                .as_object_mut()
+                .map(|root| root.entry("components").or_insert_with(|| json!({})))
+                .and_then(Value::as_object_mut)
+                .map(|components| components.entry(section).or_insert_with(|| json!({})))
+                .and_then(Value::as_object_mut)
+                .ok_or_else(|| ResolverError::TypeMismatch {
+                    ref_: format!("{COMPONENTS_REF_PREFIX}{section}/"),
+                    expected: "object".to_string(),
+                })?;
+            components.extend(hoisted);
+        }
+
+        Ok(())
+    }
+
+    pub fn walk(&self, current: &Value, doc: &Document, state: &mut WalkState) -> Result<Value> {
+        if !matches!(current, Value::Object(_) | Value::Array(_)) {
+            return Ok(current.clone());
+        }
+
+        state.depth += 1;
+        if state.depth > self.max_depth {
+            return Err(ResolverError::MaxDeptExceeded {
+                ref_: state
+                    .chain
+                    .last()
+                    .cloned()
+                    .unwrap_or_else(|| "#".to_string()),
+                depth: self.max_depth,
+                chain: state.chain.clone(),
+            }
+            .into());
+        }
+
+        let resolved = match current {
             Value::Object(map) => {
-                if let Some(Value::String(ref_str)) = map.get("ref") {
-                    return self.resolver_ref(ref_str, visited);
-                }
+                if let Some(Value::String(ref_str)) = map.get("$ref") {
+                    self.resolver_ref(ref_str, doc, state)
+                } else {
+                    let mut new_map = serde_json::Map::new();
+                    for (k, v) in map {
+                        state.path.push(k.clone());
+                        new_map.insert(k.clone(), self.walk(v, doc, state)?);
+                        state.path.pop();
+                    }
 
-                let mut new_map = serde_json::Map::new();
-                for (k, v) in map {
-                    new_map.insert(k.clone(), self.walk(v, visited)?);
+                    Ok(Value::Object(new_map))
                 }
-
-                Ok(Value::Object(new_map))
             }
 
             Value::Array(arr) => {
                 let mut new_arr = Vec::new();
 
-                for v in arr {
-                    new_arr.push(self.walk(v, visited)?);
+                for (i, v) in arr.iter().enumerate() {
+                    state.path.push(i.to_string());
+                    new_arr.push(self.walk(v, doc, state)?);
+                    state.path.pop();
                 }
 
                 Ok(Value::Array(new_arr))
             }
-            _ => Ok(current.clone()),
-        }
+            _ => unreachable!("scalars are returned before descending"),
+        };
+
+        state.depth -= 1;
+        resolved
     }
 
-    pub fn resolver_ref(&self, ref_str: &str, visited: &mut HashSet<String>) -> Result<Value> {
-        if visited.contains(ref_str) {
-            return Err(ResolverError::CycleDetected(ref_str.to_string()).into());
+    pub fn resolver_ref(
+        &self,
+        ref_str: &str,
+        doc: &Document,
+        state: &mut WalkState,
+    ) -> Result<Value> {
+        let (file, fragment) = match ref_str.find('#') {
+            Some(idx) => ref_str.split_at(idx),
+            None => (ref_str, ""),
+        };
+
+        let target_doc = if file.is_empty() {
+            doc.clone()
+        } else {
+            self.external_document(file, ref_str, doc)?
+        };
+
+        if target_doc.is_root && self.is_preserved(fragment) {
+            return Ok(json!({ "$ref": fragment }));
+        }
+
+        let key = target_key(&target_doc, fragment);
+        if let Some((section, name)) = self.hoisted.borrow().names.get(&key) {
+            return Ok(component_ref(section, name));
+        }
+
+        if state.visited.contains(&key) {
+            // Recursive schemas stay recursive through a named component;
+            // anything else looping back on itself can never be resolved.
+            if fragment.starts_with(COMPONENTS_REF_PREFIX)
+                && !fragment.starts_with(SCHEMA_REF_PREFIX)
+            {
+                return Err(ResolverError::CycleDetected(ref_str.to_string()).into());
+            }
+            let name = self.assign_name(&key, file, fragment, "schemas")?;
+            return Ok(component_ref("schemas", &name));
         }
-        visited.insert(ref_str.to_string());
 
-        let tokens = super::pointer::parse_json_pointer_ref(ref_str)?;
+        if !target_doc.is_root {
+            if let Some(section) = self.hoisted_section(fragment, &state.path) {
+                self.assign_name(&key, file, fragment, section)?;
+            }
+        }
+        state.visited.insert(key.clone());
+        state.chain.push(ref_str.to_string());
+
+        let tokens = if fragment.is_empty() {
+            Vec::new()
+        } else {
+            super::pointer::parse_json_pointer_ref(fragment)?
+        };
 
-        let mut target = &self.root;
+        let mut target = target_doc.value.as_ref();
 
         for token in tokens {
-            target = target
-                .get(&token)
-                .ok_or_else(|| ResolverError::MissingTarget {
-                    ref_: ref_str.to_string(),
-                    path: token,
-                })?;
+            target = step(target, &token).ok_or_else(|| ResolverError::MissingTarget {
+                ref_: ref_str.to_string(),
+                path: token,
+            })?;
         }
-        let resolved = self.walk(target, visited)?;
+        let resolved = self.walk(target, &target_doc, state)?;
+
+        state.chain.pop();
+        state.visited.remove(&key);
 
-        visited.remove(ref_str);
+        let mut hoisted = self.hoisted.borrow_mut();
+        if let Some((section, name)) = hoisted.names.get(&key).cloned() {
+            hoisted
+                .components
+                .entry(section)
+                .or_default()
+                .insert(name.clone(), resolved);
+            return Ok(component_ref(section, &name));
+        }
 
         Ok(resolved)
     }
+
+    /// Picks a unique name in `section` for a hoisted component from the last
+    /// token of its pointer, or the file stem when it is the whole document.
+    fn assign_name(
+        &self,
+        key: &str,
+        file: &str,
+        fragment: &str,
+        section: &'static str,
+    ) -> Result<String> {
+        let tokens = if fragment.is_empty() {
+            Vec::new()
+        } else {
+            super::pointer::parse_json_pointer_ref(fragment)?
+        };
+        let base = match tokens.last() {
+            Some(token) => token.clone(),
+            None => Path::new(file)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Schema")
+                .to_string(),
+        };
+
+        let root_components = self.root.value.pointer(&format!("/components/{section}"));
+        let mut hoisted = self.hoisted.borrow_mut();
+        let taken = |name: &str| {
+            let own_root_component = target_key(
+                &self.root,
+                &format!(
+                    "{COMPONENTS_REF_PREFIX}{section}/{}",
+                    super::pointer::escape_token(name)
+                ),
+            ) == key;
+            hoisted
+                .names
+                .values()
+                .any(|(s, n)| *s == section && n == name)
+                || (root_components.and_then(|c| c.get(name)).is_some() && !own_root_component)
+        };
+
+        let mut name = base.clone();
+        let mut n = 2;
+        while taken(&name) {
+            name = format!("{base}{n}");
+            n += 1;
+        }
+
+        hoisted
+            .names
+            .insert(key.to_string(), (section, name.clone()));
+        Ok(name)
+    }
+
+    /// Loads the file part of a ref relative to the document containing it.
+    fn external_document(&self, file: &str, ref_str: &str, doc: &Document) -> Result<Document> {
+        if file.contains("://") {
+            return Err(ResolverError::InvalidRef(ref_str.to_string()).into());
+        }
+
+        let base = doc
+            .path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let path = base.join(file);
+
+        if !path.exists() {
+            return Err(ResolverError::MissingTarget {
+                ref_: ref_str.to_string(),
+                path: path.display().to_string(),
+            }
+            .into());
+        }
+
+        let (path, value) = self.loader.load(&path)?;
+        Ok(Document {
+            is_root: self.root.path.as_ref() == Some(&path),
+            path: Some(path),
+            value,
+        })
+    }
+}
+
+/// Identifies a ref target across documents.
+fn target_key(doc: &Document, fragment: &str) -> String {
+    match &doc.path {
+        Some(path) => format!("{}{fragment}", path.display()),
+        None => fragment.to_string(),
+    }
+}
+
+fn component_ref(section: &str, name: &str) -> Value {
+    let name = super::pointer::escape_token(name);
+    json!({ "$ref": format!("{COMPONENTS_REF_PREFIX}{section}/{name}") })
+}
+
+fn component_section(name: &str) -> Option<&'static str> {
+    COMPONENT_SECTIONS.iter().copied().find(|s| *s == name)
+}
+
+/// The components section of whatever a ref at `path` stands for, judged by
+/// the keys leading to it. `None` for path items, which have no section in
+/// OpenAPI 3.0.
+fn site_section(path: &[String]) -> Option<&'static str> {
+    let key = |back: usize| path.len().checked_sub(back).map(|i| path[i].as_str());
+    // Keys right below `properties` name schema properties, not fields.
+    let field =
+        |back: usize, name: &str| key(back) == Some(name) && key(back + 1) != Some("properties");
+
+    if field(3, "components") {
+        return component_section(key(2)?);
+    }
+    if field(2, "paths") || field(3, "callbacks") {
+        return None;
+    }
+    if field(2, "parameters") && key(1)?.parse::<usize>().is_ok() {
+        return Some("parameters");
+    }
+    for section in ["responses", "headers", "examples", "links", "callbacks"] {
+        if field(2, section) {
+            return Some(section);
+        }
+    }
+    if field(1, "requestBody") {
+        return Some("requestBodies");
+    }
+    Some("schemas")
+}
+
+/// Follows a single reference token into an object member or array element.
+fn step<'a>(value: &'a Value, token: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map.get(token),
+        Value::Array(arr) => {
+            // RFC 6901 forbids leading zeros in array indices.
+            if token.len() > 1 && token.starts_with('0') {
+                return None;
+            }
+            arr.get(token.parse::<usize>().ok()?)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver_error(result: Result<Value>) -> ResolverError {
+        result.unwrap_err().downcast().unwrap()
+    }
+
+    #[test]
+    fn inlines_local_refs() {
+        let doc = json!({
+            "paths": { "/a": { "$ref": "#/x-items/0" } },
+            "x-items": [{ "$ref": "#/components/schemas/a~1b" }],
+            "components": { "schemas": { "a/b": { "type": "string" } } },
+        });
+        let resolved = Resolver::new(doc).resolve().unwrap();
+        assert_eq!(resolved["paths"]["/a"], json!({ "type": "string" }));
+    }
+
+    #[test]
+    fn keeps_preserved_refs() {
+        let doc = json!({
+            "paths": { "/a": { "$ref": "#/components/schemas/A" } },
+            "components": { "schemas": { "A": { "type": "string" } } },
+        });
+        let resolved = Resolver::new(doc)
+            .preserve(SCHEMA_REF_PREFIX)
+            .resolve()
+            .unwrap();
+        assert_eq!(
+            resolved["paths"]["/a"],
+            json!({ "$ref": "#/components/schemas/A" })
+        );
+    }
+
+    #[test]
+    fn hoists_recursive_schemas() {
+        let doc = json!({
+            "paths": { "/a": { "$ref": "#/components/schemas/Node" } },
+            "components": { "schemas": { "Node": {
+                "properties": { "next": { "$ref": "#/components/schemas/Node" } },
+            } } },
+        });
+        let resolved = Resolver::new(doc).resolve().unwrap();
+        let node = json!({ "$ref": "#/components/schemas/Node" });
+        assert_eq!(resolved["paths"]["/a"], node);
+        assert_eq!(
+            resolved["components"]["schemas"]["Node"]["properties"]["next"],
+            node
+        );
+    }
+
+    #[test]
+    fn reports_missing_targets_and_cycles() {
+        let missing = json!({ "a": { "$ref": "#/x-items/01" }, "x-items": [1, 2] });
+        assert!(matches!(
+            resolver_error(Resolver::new(missing).resolve()),
+            ResolverError::MissingTarget { path, .. } if path == "01"
+        ));
+
+        let cycle = json!({
+            "a": { "$ref": "#/components/parameters/p" },
+            "components": { "parameters": { "p": { "$ref": "#/components/parameters/p" } } },
+        });
+        assert!(matches!(
+            resolver_error(Resolver::new(cycle).resolve()),
+            ResolverError::CycleDetected(_)
+        ));
+    }
+
+    #[test]
+    fn stops_at_max_depth() {
+        let doc = json!({ "a": { "b": { "c": {} } } });
+        assert!(Resolver::new(doc.clone()).max_depth(4).resolve().is_ok());
+        assert!(matches!(
+            resolver_error(Resolver::new(doc).max_depth(3).resolve()),
+            ResolverError::MaxDeptExceeded { depth: 3, .. }
+        ));
+    }
 }
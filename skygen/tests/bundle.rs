@@ -0,0 +1,76 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::{json, Value};
+use skygen::resolver::bundle::{bundle, BundleMode};
+use skygen::resolver::resolve::DEFAULT_MAX_DEPTH;
+use std::path::PathBuf;
+
+fn spec(mode: BundleMode) -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/split/root.yaml");
+    bundle(path, mode, DEFAULT_MAX_DEPTH).unwrap()
+}
+
+fn component(section: &str, name: &str) -> Value {
+    json!({ "$ref": format!("#/components/{section}/{name}") })
+}
+
+#[test]
+fn bundled_moves_external_targets_into_components() {
+    let spec = spec(BundleMode::Bundled);
+    let get = &spec["paths"]["/droplets"]["get"];
+    let post = &spec["paths"]["/droplets"]["post"];
+
+    assert_eq!(get["parameters"][0], component("parameters", "page"));
+    assert_eq!(get["responses"]["404"], component("responses", "NotFound"));
+    assert_eq!(
+        get["responses"]["200"]["content"]["application/json"]["schema"]["items"],
+        component("schemas", "droplet")
+    );
+    assert_eq!(post["requestBody"], component("requestBodies", "create"));
+    assert_eq!(
+        post["responses"]["201"]["headers"]["X-Id"],
+        component("headers", "XId")
+    );
+
+    let components = &spec["components"];
+    assert_eq!(components["parameters"]["page"]["in"], "query");
+    assert_eq!(
+        components["responses"]["NotFound"]["content"]["application/json"]["schema"],
+        component("schemas", "error")
+    );
+    // A property named like a section is still a schema.
+    let droplet = &components["schemas"]["droplet"]["properties"];
+    assert_eq!(droplet["parameters"], component("schemas", "region"));
+    assert_eq!(
+        droplet["children"]["items"],
+        component("schemas", "droplet")
+    );
+    assert_eq!(
+        components["schemas"]["Existing"]["properties"]["d"],
+        component("schemas", "droplet")
+    );
+}
+
+#[test]
+fn dereferenced_inlines_everything_but_recursion() {
+    let spec = spec(BundleMode::Dereferenced);
+    let get = &spec["paths"]["/droplets"]["get"];
+
+    assert_eq!(get["parameters"][0]["name"], "page");
+    assert_eq!(get["responses"]["404"]["description"], "not found");
+    let components = spec["components"].as_object().unwrap();
+    let sections: Vec<&str> = components.keys().map(String::as_str).collect();
+    assert_eq!(sections, ["schemas"]);
+}
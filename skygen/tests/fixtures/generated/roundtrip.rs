@@ -0,0 +1,103 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Copied into the crate generated from `spec.yaml` as an integration test.
+
+use reqwest::{Method, Request};
+use serde_json::json;
+use skygen_golden::models::result::ResultValue;
+use skygen_golden::models::status::Status;
+use skygen_golden::models::value::Value;
+use skygen_golden::{ChainProvider, Client, Credentials, Error};
+
+#[test]
+fn result_round_trips() {
+    let wire = json!({
+        "id": "r-1",
+        "status": "on",
+        "tags": ["a", "b"],
+        "type": "self",
+        "value": { "kind": "text", "text": "hello" },
+    });
+    let result: ResultValue = serde_json::from_value(wire.clone()).unwrap();
+
+    assert!(matches!(result.status, Some(Status::On)));
+    assert_eq!(
+        result.tags.as_deref(),
+        Some(&["a".to_string(), "b".to_string()][..])
+    );
+    assert!(
+        matches!(&result.value, Some(Value::Text(text)) if text.text.as_deref() == Some("hello"))
+    );
+    assert_eq!(serde_json::to_value(&result).unwrap(), wire);
+}
+
+#[test]
+fn tagged_unions_round_trip() {
+    let wire = json!({ "kind": "number", "number": 1.5 });
+    let value: Value = serde_json::from_value(wire.clone()).unwrap();
+
+    assert!(matches!(&value, Value::Number(number) if number.number == Some(1.5)));
+    assert_eq!(serde_json::to_value(&value).unwrap(), wire);
+    assert!(serde_json::from_value::<Value>(json!({ "kind": "other" })).is_err());
+}
+
+#[test]
+fn unlisted_enum_values_round_trip() {
+    let status: Status = serde_json::from_value(json!("standby")).unwrap();
+
+    assert!(matches!(&status, Status::Unknown(value) if value == "standby"));
+    assert_eq!(status.as_str(), "standby");
+    assert_eq!(serde_json::to_value(&status).unwrap(), json!("standby"));
+    assert!(matches!("off".parse(), Ok(Status::Off)));
+}
+
+const OPTIONAL_BEARER: &[&[&str]] = &[&[], &["bearer"]];
+
+fn authorization(client: &Client, security: &'static [&'static [&'static str]]) -> Option<String> {
+    let mut req = Request::new(Method::GET, client.join_url("/results").unwrap());
+    client.authorize(&mut req, security).unwrap();
+    req.headers()
+        .get("authorization")
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[test]
+fn credentials_win_over_anonymous_alternative() {
+    let client = Client::builder()
+        .credentials(Credentials::Bearer("token".into()))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        authorization(&client, OPTIONAL_BEARER).as_deref(),
+        Some("Bearer token")
+    );
+}
+
+#[test]
+fn anonymous_alternative_without_credentials() {
+    let client = Client::builder()
+        .credential_provider(ChainProvider::default())
+        .build()
+        .unwrap();
+
+    assert_eq!(authorization(&client, OPTIONAL_BEARER), None);
+
+    let mut req = Request::new(Method::GET, client.join_url("/results").unwrap());
+    assert!(matches!(
+        client.authorize(&mut req, &[&["bearer"]]),
+        Err(Error::MissingCredentials(_))
+    ));
+}
@@ -0,0 +1,83 @@
+// Copyright 2025 Cloudflavor GmbH
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates a crate from `tests/fixtures/generated` and builds it, so the
+//! templates and runtime assets are checked by the compiler, clippy and their
+//! own unit tests.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FEATURES: &str = "validation,tracing,profile";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/generated")
+        .join(name)
+}
+
+/// Runs a cargo `command` in the generated crate, followed by `args`.
+/// Dependencies are looked up offline first so the test needs no network
+/// once they are cached.
+fn cargo(dir: &Path, command: &str, args: &[&str]) {
+    let run = |offline: bool| {
+        let mut cmd = Command::new(env!("CARGO"));
+        cmd.args([command, "--features", FEATURES])
+            .current_dir(dir)
+            .env(
+                "CARGO_TARGET_DIR",
+                dir.with_file_name("generated-crate-target"),
+            );
+        if offline {
+            cmd.arg("--offline");
+        }
+        cmd.args(args).output().expect("failed to run cargo")
+    };
+
+    let output = match run(true) {
+        output if output.status.success() => output,
+        _ => run(false),
+    };
+    assert!(
+        output.status.success(),
+        "cargo {command} {} failed:\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn generated_crate_builds() {
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("generated-crate");
+    if out.exists() {
+        std::fs::remove_dir_all(&out).unwrap();
+    }
+
+    let config = std::fs::read_to_string(fixture("config.toml")).unwrap();
+    let config: skygen::Config = toml::from_str(&config).unwrap();
+    let spec = skygen::load_spec(fixture("spec.yaml"), config.max_depth()).unwrap();
+    skygen::generate_blocking(&config, &spec, &out).unwrap();
+    std::fs::create_dir_all(out.join("tests")).unwrap();
+    std::fs::copy(fixture("roundtrip.rs"), out.join("tests/roundtrip.rs")).unwrap();
+
+    // Schemas named after prelude types must not shadow them.
+    let result = std::fs::read_to_string(out.join("src/models/result.rs")).unwrap();
+    assert!(result.contains("pub struct ResultValue"));
+    assert!(result.contains("pub r#type: Option<Kind>"));
+    let option = std::fs::read_to_string(out.join("src/models/option.rs")).unwrap();
+    assert!(option.contains("pub struct OptionValue"));
+
+    cargo(&out, "clippy", &["--all-targets", "--", "-D", "warnings"]);
+    cargo(&out, "test", &[]);
+}